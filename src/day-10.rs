@@ -1,4 +1,4 @@
-use std::{fmt::Display, fs::read_to_string};
+use std::{fmt::Display, fs::read_to_string, io::BufRead};
 
 #[derive(Debug, PartialEq)]
 enum Token {
@@ -112,19 +112,25 @@ mod part_1 {
         for line in chunk_lines {
             let result = parse_chunk_line(line);
             match result {
-                Err(ChunkParsingError::CorruptedLine { found, .. }) => match found {
-                    ')' => score += 3,
-                    ']' => score += 57,
-                    '}' => score += 1197,
-                    '>' => score += 25137,
-                    _ => continue,
-                },
+                Err(ChunkParsingError::CorruptedLine { found, .. }) => {
+                    score += get_illegal_delim_score(found)
+                }
                 _ => continue,
             }
         }
 
         score
     }
+
+    pub fn get_illegal_delim_score(c: char) -> u64 {
+        match c {
+            ')' => 3,
+            ']' => 57,
+            '}' => 1197,
+            '>' => 25137,
+            _ => 0,
+        }
+    }
 }
 
 mod part_2 {
@@ -136,13 +142,7 @@ mod part_2 {
         for line in chunk_lines {
             let result = parse_chunk_line(line);
             match result {
-                Ok(stack) => {
-                    let missing_delims = stack.iter()
-                        .rev()
-                        .map(|t| get_matching_close_delim(t.char()))
-                        .collect::<Vec<char>>();
-                    line_scores.push(calculate_line_score(&missing_delims));
-                },
+                Ok(stack) => line_scores.push(calculate_completion_score(&stack)),
                 _ => continue
             }
         }
@@ -151,6 +151,15 @@ mod part_2 {
         line_scores[line_scores.len() / 2]
     }
 
+    pub fn calculate_completion_score(stack: &[Token]) -> u64 {
+        let missing_delims = stack
+            .iter()
+            .rev()
+            .map(|t| get_matching_close_delim(t.char()))
+            .collect::<Vec<char>>();
+        calculate_line_score(&missing_delims)
+    }
+
     fn calculate_line_score(missing_delims: &[char]) -> u64 {
         let mut score = 0u64;
 
@@ -172,6 +181,28 @@ mod part_2 {
     }
 }
 
+// Classifies chunk lines as they are read, without keeping them all in memory.
+// Returns the syntax error score of the corrupted lines along with the
+// completion scores of the incomplete ones, in the order they were read.
+// Stops at the first line that can't be read.
+pub fn classify_reader<R: BufRead>(reader: R) -> std::io::Result<(u64, Vec<u64>)> {
+    let mut syntax_error_score = 0u64;
+    let mut completion_scores: Vec<u64> = Vec::new();
+
+    for line in reader.lines() {
+        let chunk_line = ChunkLine(line?);
+        match parse_chunk_line(&chunk_line) {
+            Err(ChunkParsingError::CorruptedLine { found, .. }) => {
+                syntax_error_score += part_1::get_illegal_delim_score(found)
+            }
+            Ok(stack) => completion_scores.push(part_2::calculate_completion_score(&stack)),
+            _ => continue,
+        }
+    }
+
+    Ok((syntax_error_score, completion_scores))
+}
+
 fn main() {
     let chunk_lines = parse_input(&read_to_string("data/day-10.txt").unwrap());
 
@@ -268,4 +299,46 @@ mod tests {
         assert_eq!(score, 288957);
     }
 
+    #[test]
+    fn classification_from_reader_matches_batch_results() {
+        const INPUT: &str = "[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]";
+
+        let (syntax_error_score, mut completion_scores) =
+            classify_reader(std::io::Cursor::new(INPUT)).unwrap();
+
+        let chunk_lines = parse_input(INPUT);
+        assert_eq!(
+            syntax_error_score,
+            part_1::calculate_syntax_error_score_for_corrupted_lines(&chunk_lines)
+        );
+        assert_eq!(syntax_error_score, 26397);
+
+        assert_eq!(
+            completion_scores,
+            vec![288957, 5566, 1480781, 995444, 294]
+        );
+        completion_scores.sort();
+        assert_eq!(
+            completion_scores[completion_scores.len() / 2],
+            part_2::calculate_completion_score_for_incomplete_lines(&chunk_lines)
+        );
+    }
+
+    #[test]
+    fn classifying_from_a_reader_that_fails() {
+        // Not valid UTF-8, so reading the second line fails
+        let input: &[u8] = b"{([(<{}[<>[]}>{[]{[(<()>\n\xff\n";
+        let result = classify_reader(std::io::Cursor::new(input));
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+
 }
@@ -1,19 +1,72 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
 use std::fs::read_to_string;
 
 type Octopuses = Vec<Vec<u8>>;
 
+const FLASH_THRESHOLD: u8 = 9;
+const RESET_ENERGY_LEVEL: u8 = 0;
+
+#[derive(Debug, PartialEq)]
+struct Point(usize, usize);
+
 #[derive(Debug, PartialEq)]
-struct Point(i16, i16);
+enum InputParsingError {
+    InvalidEnergyLevel {
+        row: usize,
+        col: usize,
+        found: char,
+    },
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
 
-fn parse_input(input: &str) -> Octopuses {
+impl Display for InputParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidEnergyLevel { row, col, found } => {
+                write!(
+                    f,
+                    "Invalid energy level {found:?} at row {row}, column {col}"
+                )
+            }
+            Self::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(f, "Row {row} has {found} octopuses; expected {expected}"),
+        }
+    }
+}
+
+fn parse_input(input: &str) -> Result<Octopuses, InputParsingError> {
     let mut octopuses: Octopuses = Vec::new();
 
     let lines = input.split_terminator("\n");
-    for line in lines {
-        octopuses.push(Vec::from_iter(line.bytes().map(|b| (b - 48) as u8)));
+    for (row, line) in lines.enumerate() {
+        let mut energy_levels: Vec<u8> = Vec::new();
+        for (col, c) in line.chars().enumerate() {
+            match c.to_digit(10) {
+                Some(energy_level) => energy_levels.push(energy_level as u8),
+                None => return Err(InputParsingError::InvalidEnergyLevel { row, col, found: c }),
+            }
+        }
+        if let Some(first_row) = octopuses.first() {
+            if energy_levels.len() != first_row.len() {
+                return Err(InputParsingError::RaggedRow {
+                    row,
+                    expected: first_row.len(),
+                    found: energy_levels.len(),
+                });
+            }
+        }
+        octopuses.push(energy_levels);
     }
 
-    octopuses
+    Ok(octopuses)
 }
 
 fn _print_octopuses(octopuses: &Octopuses) {
@@ -22,34 +75,32 @@ fn _print_octopuses(octopuses: &Octopuses) {
     }
 }
 
-fn get_adjacent_points(octopuses: &Octopuses, base: Point) -> Vec<Point> {
-    let mut points: Vec<Point> = Vec::new();
-    let rows = octopuses.len() as i16;
-    let cols = octopuses[0].len() as i16;
-    let r = base.0;
-    let c = base.1;
+// Positions adjacent to (row, col), including diagonals, on a grid with the
+// given dimensions
+pub fn adjacent_positions(rows: usize, cols: usize, row: usize, col: usize) -> Vec<(usize, usize)> {
+    let mut positions: Vec<(usize, usize)> = Vec::new();
+    let rows = rows as isize;
+    let cols = cols as isize;
+    let r = row as isize;
+    let c = col as isize;
 
     for i in r - 1..(r + 2) {
         for j in c - 1..(c + 2) {
-            points.push(Point(i, j));
+            if i < 0 || j < 0 || i >= rows || j >= cols || (i == r && j == c) {
+                continue;
+            }
+            positions.push((i as usize, j as usize));
         }
     }
 
-    points.retain(|p| p.0 > -1 && p.1 > -1 && p.0 < rows && p.1 < cols && *p != base);
-
-    points
+    positions
 }
 
-fn flash(octopuses: &mut Octopuses, base: Point, flashed: &[Point]) {
-    let points = get_adjacent_points(octopuses, base);
-    for point in points {
-        if flashed.contains(&point) {
-            continue;
-        }
-        let row = point.0 as usize;
-        let col = point.1 as usize;
-        octopuses[row][col] += 1;
-    }
+fn get_adjacent_points(octopuses: &Octopuses, base: Point) -> Vec<Point> {
+    adjacent_positions(octopuses.len(), octopuses[0].len(), base.0, base.1)
+        .into_iter()
+        .map(|(row, col)| Point(row, col))
+        .collect()
 }
 
 fn increment_all_energy_levels(octopuses: &mut Octopuses) {
@@ -60,76 +111,163 @@ fn increment_all_energy_levels(octopuses: &mut Octopuses) {
     }
 }
 
-fn flash_em_up(octopuses: &mut Octopuses) -> u64 {
+fn flash_em_up(octopuses: &mut Octopuses, threshold: u8, reset: u8) -> u64 {
     let mut flash_count = 0u64;
-    let mut flashed: Vec<Point> = Vec::new();
-
-    loop {
-        let orig_flash_count = flash_count;
-        for row in 0..octopuses.len() {
-            for col in 0..octopuses[0].len() {
-                let point = Point(row as i16, col as i16);
-                if flashed.contains(&point) {
-                    continue;
-                }
-                if octopuses[row][col] > 9 {
-                    flash(octopuses, Point(row as i16, col as i16), &flashed);
-                    flashed.push(point);
-                    flash_count += 1;
-                    octopuses[row][col] = 0;
-                }
+    let mut flashed = vec![vec![false; octopuses[0].len()]; octopuses.len()];
+    let mut queue: VecDeque<Point> = VecDeque::new();
+
+    for row in 0..octopuses.len() {
+        for col in 0..octopuses[0].len() {
+            if octopuses[row][col] > threshold {
+                flashed[row][col] = true;
+                queue.push_back(Point(row, col));
             }
         }
-        if flash_count == orig_flash_count {
-            break;
+    }
+
+    while let Some(base) = queue.pop_front() {
+        octopuses[base.0][base.1] = reset;
+        flash_count += 1;
+
+        for point in get_adjacent_points(octopuses, base) {
+            let row = point.0;
+            let col = point.1;
+            if flashed[row][col] {
+                continue;
+            }
+            octopuses[row][col] += 1;
+            if octopuses[row][col] > threshold {
+                flashed[row][col] = true;
+                queue.push_back(point);
+            }
         }
     }
 
     flash_count
 }
 
-mod part_1 {
+pub fn flashes_per_step(mut octopuses: Octopuses, steps: u32) -> Vec<u64> {
+    let mut flashes: Vec<u64> = Vec::new();
+    for _ in 0..steps {
+        increment_all_energy_levels(&mut octopuses);
+        flashes.push(flash_em_up(
+            &mut octopuses,
+            FLASH_THRESHOLD,
+            RESET_ENERGY_LEVEL,
+        ));
+    }
+    flashes
+}
+
+pub fn simulate(octopuses: Octopuses, steps: u32) -> (Octopuses, u64) {
+    simulate_with_params(octopuses, steps, FLASH_THRESHOLD, RESET_ENERGY_LEVEL)
+}
+
+// Octopuses flash once their energy level goes above `threshold` and then have
+// their energy level set to `reset`
+pub fn simulate_with_params(
+    mut octopuses: Octopuses,
+    steps: u32,
+    threshold: u8,
+    reset: u8,
+) -> (Octopuses, u64) {
+    let mut total_flashes = 0u64;
+    for _ in 0..steps {
+        increment_all_energy_levels(&mut octopuses);
+        total_flashes += flash_em_up(&mut octopuses, threshold, reset);
+    }
+    (octopuses, total_flashes)
+}
+
+pub mod part_1 {
     use super::*;
 
-    pub fn count_total_flashes(mut octopuses: Octopuses, steps: u8) -> u64 {
-        let mut total_flashes = 0u64;
-        for _ in 0..steps {
-            increment_all_energy_levels(&mut octopuses);
-            total_flashes += flash_em_up(&mut octopuses);
+    pub fn count_total_flashes(octopuses: Octopuses, steps: u32) -> u64 {
+        simulate(octopuses, steps).1
+    }
+}
+
+pub fn first_step_with_flash_fraction(
+    mut octopuses: Octopuses,
+    fraction: f64,
+    cap: u32,
+) -> Option<u32> {
+    let octopuses_count: usize = octopuses.iter().map(Vec::len).sum();
+    if octopuses_count == 0 {
+        return None;
+    }
+    let octopuses_count = octopuses_count as f64;
+    for s in 0..cap {
+        increment_all_energy_levels(&mut octopuses);
+        let flash_count = flash_em_up(&mut octopuses, FLASH_THRESHOLD, RESET_ENERGY_LEVEL);
+        if flash_count as f64 >= fraction * octopuses_count {
+            return Some(s + 1);
         }
-        total_flashes
     }
+    None
 }
 
-mod part_2 {
+pub mod part_2 {
     use super::*;
 
     pub fn find_first_step_where_all_flash_simultaneously(
-        mut octopuses: Octopuses,
-        steps: u8,
-    ) -> u8 {
-        let octopuses_count = octopuses.len() * octopuses[0].len();
-        for s in 0..steps {
-            increment_all_energy_levels(&mut octopuses);
-            let flash_count = flash_em_up(&mut octopuses);
-            if flash_count == octopuses_count as u64 {
-                return s + 1;
-            }
+        octopuses: Octopuses,
+        steps: u32,
+    ) -> Option<u32> {
+        first_step_with_flash_fraction(octopuses, 1.0, steps)
+    }
+}
+
+const PART_1_STEPS: u32 = 100;
+// Step cap for the search in main; the puzzle input synchronizes well before it
+const MAX_STEPS: u32 = 1000;
+
+// Runs a single simulation, long enough to find the first step where all
+// octopuses flash simultaneously (giving up after `cap` steps), collecting the
+// flashes of the first 100 steps along the way. An empty grid has neither
+// flashes nor such a step
+pub fn solve(octopuses: &Octopuses, cap: u32) -> (u64, Option<u32>) {
+    let octopuses_count: usize = octopuses.iter().map(Vec::len).sum();
+    if octopuses_count == 0 {
+        return (0, None);
+    }
+    let mut octopuses = octopuses.clone();
+    let mut total_flashes = 0u64;
+    let mut all_flash_step: Option<u32> = None;
+
+    for step in 1..=cap.max(PART_1_STEPS) {
+        increment_all_energy_levels(&mut octopuses);
+        let flash_count = flash_em_up(&mut octopuses, FLASH_THRESHOLD, RESET_ENERGY_LEVEL);
+        if step <= PART_1_STEPS {
+            total_flashes += flash_count;
+        }
+        if all_flash_step.is_none() && step <= cap && flash_count == octopuses_count as u64 {
+            all_flash_step = Some(step);
+        }
+        if step >= PART_1_STEPS && all_flash_step.is_some() {
+            break;
         }
-        return 0;
     }
+
+    (total_flashes, all_flash_step)
 }
 
 fn main() {
-    let octopuses = parse_input(&read_to_string("data/day-11.txt").unwrap());
+    let octopuses = match parse_input(&read_to_string("data/day-11.txt").unwrap()) {
+        Ok(octopuses) => octopuses,
+        Err(e) => panic!("Error parsing input file for day 11: {}", e),
+    };
+
+    let (total_flashes, all_flash_step) = solve(&octopuses, MAX_STEPS);
 
     println!("== PART 1");
-    let total_flashes = part_1::count_total_flashes(octopuses.clone(), 100);
-    println!("Total flashes after 100 steps: {total_flashes}");
+    println!("Total flashes after {PART_1_STEPS} steps: {total_flashes}");
 
     println!("== PART 2");
-    let step = part_2::find_first_step_where_all_flash_simultaneously(octopuses.clone(), 255);
-    println!("First step where all flash simultaneously: {step}");
+    match all_flash_step {
+        Some(step) => println!("First step where all flash simultaneously: {step}"),
+        None => println!("No step where all flash simultaneously within {MAX_STEPS} steps"),
+    }
 }
 
 #[cfg(test)]
@@ -141,25 +279,106 @@ mod tests {
 032265
 235498";
 
+    const EXAMPLE_INPUT: &str = "5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526";
+
+    fn flash(octopuses: &mut Octopuses, base: Point, flashed: &[Point]) {
+        let points = get_adjacent_points(octopuses, base);
+        for point in points {
+            if flashed.contains(&point) {
+                continue;
+            }
+            let row = point.0;
+            let col = point.1;
+            octopuses[row][col] += 1;
+        }
+    }
+
+    // Original implementation, which rescans the whole grid until no new flashes occur
+    fn rescanning_flash_em_up(octopuses: &mut Octopuses) -> u64 {
+        let mut flash_count = 0u64;
+        let mut flashed: Vec<Point> = Vec::new();
+
+        loop {
+            let orig_flash_count = flash_count;
+            for row in 0..octopuses.len() {
+                for col in 0..octopuses[0].len() {
+                    let point = Point(row, col);
+                    if flashed.contains(&point) {
+                        continue;
+                    }
+                    if octopuses[row][col] > 9 {
+                        flash(octopuses, Point(row, col), &flashed);
+                        flashed.push(point);
+                        flash_count += 1;
+                        octopuses[row][col] = 0;
+                    }
+                }
+            }
+            if flash_count == orig_flash_count {
+                break;
+            }
+        }
+
+        flash_count
+    }
+
     #[test]
     fn input_parsing_into_2d_vec() {
         let input = "123
 456";
-        let octopuses = parse_input(input);
+        let octopuses = parse_input(input).unwrap();
         assert_eq!(octopuses.len(), 2);
         assert_eq!(octopuses[0].len(), 3);
     }
 
+    #[test]
+    fn input_parsing_with_non_digit_character() {
+        let input = "123
+4x6";
+        assert_eq!(
+            parse_input(input),
+            Err(InputParsingError::InvalidEnergyLevel {
+                row: 1,
+                col: 1,
+                found: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn input_parsing_with_ragged_grid() {
+        let input = "123
+45
+789";
+        assert_eq!(
+            parse_input(input),
+            Err(InputParsingError::RaggedRow {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
     #[test]
     fn finding_adjacent_points_of_the_left_corner_point() {
-        let octopuses = parse_input(INPUT);
+        let octopuses = parse_input(INPUT).unwrap();
         let points = get_adjacent_points(&octopuses, Point(0, 0));
         assert_eq!(points, vec![Point(0, 1), Point(1, 0), Point(1, 1)]);
     }
 
     #[test]
     fn finding_adjacent_points_of_a_middle_point() {
-        let octopuses = parse_input(INPUT);
+        let octopuses = parse_input(INPUT).unwrap();
         let points = get_adjacent_points(&octopuses, Point(2, 3));
         assert_eq!(
             points,
@@ -178,14 +397,55 @@ mod tests {
 
     #[test]
     fn finding_adjacent_points_of_the_right_corner_point() {
-        let octopuses = parse_input(INPUT);
+        let octopuses = parse_input(INPUT).unwrap();
         let points = get_adjacent_points(&octopuses, Point(3, 5));
         assert_eq!(points, vec![Point(2, 4), Point(2, 5), Point(3, 4)]);
     }
 
+    #[test]
+    fn finding_adjacent_points_beyond_i16_range() {
+        let cols = i16::MAX as usize + 10;
+        let octopuses: Octopuses = vec![vec![0; cols]; 2];
+        let points = get_adjacent_points(&octopuses, Point(1, cols - 1));
+        assert_eq!(
+            points,
+            vec![Point(0, cols - 2), Point(0, cols - 1), Point(1, cols - 2)]
+        );
+    }
+
+    #[test]
+    fn adjacent_positions_of_a_corner_cell() {
+        assert_eq!(adjacent_positions(4, 6, 0, 5), vec![(0, 4), (1, 4), (1, 5)]);
+    }
+
+    #[test]
+    fn adjacent_positions_of_an_edge_cell() {
+        assert_eq!(
+            adjacent_positions(4, 6, 3, 2),
+            vec![(2, 1), (2, 2), (2, 3), (3, 1), (3, 3)]
+        );
+    }
+
+    #[test]
+    fn adjacent_positions_of_a_center_cell() {
+        assert_eq!(
+            adjacent_positions(4, 6, 1, 1),
+            vec![
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 2),
+                (2, 0),
+                (2, 1),
+                (2, 2)
+            ]
+        );
+    }
+
     #[test]
     fn increment_of_all_energy_levels() {
-        let mut octopuses = parse_input(INPUT);
+        let mut octopuses = parse_input(INPUT).unwrap();
         increment_all_energy_levels(&mut octopuses);
         assert_eq!(octopuses[0], [3, 4, 8, 2, 7, 4]);
         assert_eq!(octopuses[3], [3, 4, 6, 5, 10, 9]);
@@ -195,25 +455,145 @@ mod tests {
     fn total_number_of_flashes() {
         let input = "183
 456";
-        let octopuses = parse_input(input);
+        let octopuses = parse_input(input).unwrap();
         let total_flashes = part_1::count_total_flashes(octopuses, 3);
         assert_eq!(total_flashes, 3);
     }
 
     #[test]
     fn total_number_of_flashes_problem_example() {
-        let input = "5483143223
-2745854711
-5264556173
-6141336146
-6357385478
-4167524645
-2176841721
-6882881134
-4846848554
-5283751526";
-        let octopuses = parse_input(input);
+        let octopuses = parse_input(EXAMPLE_INPUT).unwrap();
         let total_flashes = part_1::count_total_flashes(octopuses, 100);
         assert_eq!(total_flashes, 1656);
     }
+
+    #[test]
+    fn first_step_where_all_flash_simultaneously_beyond_255_steps() {
+        let input = "421368
+643147
+873904
+569255
+728172
+206221";
+        let octopuses = parse_input(input).unwrap();
+        let step = part_2::find_first_step_where_all_flash_simultaneously(octopuses, 1000);
+        assert_eq!(step, Some(300));
+    }
+
+    #[test]
+    fn first_step_where_all_flash_simultaneously_problem_example() {
+        let octopuses = parse_input(EXAMPLE_INPUT).unwrap();
+        let step = part_2::find_first_step_where_all_flash_simultaneously(octopuses, 1000);
+        assert_eq!(step, Some(195));
+    }
+
+    #[test]
+    fn no_step_where_all_flash_simultaneously_within_cap() {
+        let octopuses = parse_input(EXAMPLE_INPUT).unwrap();
+        let step = part_2::find_first_step_where_all_flash_simultaneously(octopuses, 194);
+        assert_eq!(step, None);
+    }
+
+    #[test]
+    fn flashes_per_step_problem_example() {
+        let octopuses = parse_input(EXAMPLE_INPUT).unwrap();
+        let flashes = flashes_per_step(octopuses.clone(), 10);
+        assert_eq!(flashes.len(), 10);
+        assert_eq!(flashes[..5], [0, 35, 45, 16, 8]);
+        assert_eq!(
+            flashes.iter().sum::<u64>(),
+            part_1::count_total_flashes(octopuses, 10)
+        );
+    }
+
+    #[test]
+    fn queue_driven_flashing_matches_rescanning_flashing() {
+        let mut octopuses = parse_input(EXAMPLE_INPUT).unwrap();
+        let mut expected_octopuses = octopuses.clone();
+        for _ in 0..100 {
+            increment_all_energy_levels(&mut octopuses);
+            increment_all_energy_levels(&mut expected_octopuses);
+            assert_eq!(
+                flash_em_up(&mut octopuses, FLASH_THRESHOLD, RESET_ENERGY_LEVEL),
+                rescanning_flash_em_up(&mut expected_octopuses)
+            );
+            assert_eq!(octopuses, expected_octopuses);
+        }
+    }
+
+    #[test]
+    fn grid_state_after_2_steps_problem_example() {
+        let octopuses = parse_input(EXAMPLE_INPUT).unwrap();
+        let (octopuses, total_flashes) = simulate(octopuses, 2);
+        let expected = parse_input(
+            "8807476555
+5089087054
+8597889608
+8485769600
+8700908800
+6600088989
+6800005943
+0000007456
+9000000876
+8700006848",
+        )
+        .unwrap();
+        assert_eq!(octopuses, expected);
+        assert_eq!(total_flashes, 35);
+    }
+
+    #[test]
+    fn lower_flash_threshold_produces_more_flashes() {
+        let input = "183
+456";
+        let octopuses = parse_input(input).unwrap();
+        let (_, standard_flashes) = simulate(octopuses.clone(), 3);
+        let (octopuses, total_flashes) = simulate_with_params(octopuses, 3, 5, 2);
+        assert_eq!(standard_flashes, 3);
+        assert_eq!(total_flashes, 6);
+        assert_eq!(octopuses, vec![vec![3, 5, 4], vec![5, 5, 4]]);
+    }
+
+    #[test]
+    fn first_step_where_half_flash_problem_example() {
+        let octopuses = parse_input(EXAMPLE_INPUT).unwrap();
+        assert_eq!(
+            first_step_with_flash_fraction(octopuses.clone(), 0.5, 1000),
+            Some(87)
+        );
+        assert_eq!(first_step_with_flash_fraction(octopuses, 0.5, 86), None);
+    }
+
+    #[test]
+    fn flash_fraction_of_an_empty_grid() {
+        assert_eq!(first_step_with_flash_fraction(vec![], 1.0, 1000), None);
+        assert_eq!(
+            first_step_with_flash_fraction(vec![vec![]], 0.5, 1000),
+            None
+        );
+    }
+
+    #[test]
+    fn solving_both_parts_in_one_call_problem_example() {
+        let octopuses = parse_input(EXAMPLE_INPUT).unwrap();
+        assert_eq!(solve(&octopuses, 1000), (1656, Some(195)));
+        assert_eq!(solve(&octopuses, 194), (1656, None));
+    }
+
+    #[test]
+    fn solving_an_empty_grid() {
+        assert_eq!(solve(&parse_input("").unwrap(), 1000), (0, None));
+        assert_eq!(solve(&vec![vec![]], 1000), (0, None));
+    }
+
+    #[test]
+    fn solving_a_grid_that_never_synchronizes() {
+        // The third octopus never falls into step with the other two
+        let octopuses = parse_input("002\n").unwrap();
+        let (total_flashes, all_flash_step) = solve(&octopuses, 1000);
+        assert_eq!(all_flash_step, None);
+        assert!(total_flashes > 0);
+        let octopuses = parse_input("000\n").unwrap();
+        assert_eq!(solve(&octopuses, 1000).1, Some(10));
+    }
 }
@@ -1,9 +1,6 @@
-use std::cell::RefCell;
 use std::collections::HashMap;
-use std::collections::VecDeque;
 use std::fmt::Display;
 use std::fs::read_to_string;
-use std::rc::Rc;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum CaveType {
@@ -17,13 +14,6 @@ pub enum CaveType {
 pub struct Cave {
     id: String,
     ctype: CaveType,
-    connections: Vec<Rc<RefCell<Cave>>>,
-}
-
-impl PartialEq for Cave {
-    fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
-    }
 }
 
 impl Display for Cave {
@@ -33,12 +23,11 @@ impl Display for Cave {
 }
 
 impl Cave {
-    fn new(id: &str) -> Rc<RefCell<Cave>> {
-        Rc::new(RefCell::new(Cave {
+    fn new(id: &str) -> Cave {
+        Cave {
             id: id.to_string(),
             ctype: Cave::get_cave_type(id),
-            connections: Vec::new(),
-        }))
+        }
     }
 
     fn get_cave_type(id: &str) -> CaveType {
@@ -52,205 +41,502 @@ impl Cave {
             CaveType::Small
         }
     }
+}
+
+// Caves are interned into indices; connections[i] holds the indices of the
+// caves connected to caves[i]
+#[derive(Debug)]
+pub struct Graph {
+    caves: Vec<Cave>,
+    connections: Vec<Vec<usize>>,
+    start: usize,
+}
+
+pub fn cave_type(graph: &Graph, id: &str) -> Option<CaveType> {
+    graph
+        .caves
+        .iter()
+        .find(|cave| cave.id == id)
+        .map(|cave| cave.ctype.clone())
+}
+
+// Graphviz DOT representation of the cave system; big caves are drawn as
+// ellipses, small caves as boxes and start/end as double circles
+pub fn to_dot(cave_system: &Graph) -> String {
+    let mut dot = String::from("graph caves {\n");
+
+    for cave in cave_system.caves.iter() {
+        let shape = match cave.ctype {
+            CaveType::Start | CaveType::End => "doublecircle",
+            CaveType::Big => "ellipse",
+            CaveType::Small => "box",
+        };
+        dot.push_str(&format!("    \"{}\" [shape={}];\n", cave.id, shape));
+    }
 
-    fn print(cave_system: Rc<RefCell<Cave>>) {
-        let mut to_print: VecDeque<Rc<RefCell<Cave>>> = VecDeque::new();
-        let mut visited: CaveList = Vec::new();
-        let mut cave = Rc::clone(&cave_system);
-        to_print.push_back(Rc::clone(&cave));
-        visited.push(Rc::clone(&cave));
-        while !to_print.is_empty() {
-            cave = to_print.pop_front().unwrap();
-            println!("{}", cave.borrow());
-            for c in cave.borrow().connections.iter() {
-                if !visited.contains(c) {
-                    to_print.push_back(Rc::clone(c));
+    for (i, connections) in cave_system.connections.iter().enumerate() {
+        // A self-loop is pushed twice onto its own cave's list; emit every
+        // other occurrence so each one shows up once
+        let mut pending_self_loop = false;
+        for &j in connections.iter().filter(|&&j| i <= j) {
+            if j == i {
+                pending_self_loop = !pending_self_loop;
+                if !pending_self_loop {
+                    continue;
                 }
             }
+            dot.push_str(&format!(
+                "    \"{}\" -- \"{}\";\n",
+                cave_system.caves[i].id, cave_system.caves[j].id
+            ));
         }
     }
+
+    dot.push_str("}\n");
+    dot
 }
 
-type CaveList = Vec<Rc<RefCell<Cave>>>;
+type CaveList = Vec<usize>;
+
+pub fn path_strings(cave_system: &Graph) -> Vec<String> {
+    part_1::get_cave_paths(cave_system)
+        .iter()
+        .map(|path| {
+            path.iter()
+                .map(|&cave| cave_system.caves[cave].id.as_str())
+                .collect::<Vec<&str>>()
+                .join(",")
+        })
+        .collect()
+}
 
-fn print_cave_list(cave_list: &CaveList) {
-    for cave in cave_list {
-        print!("{} ", cave.borrow());
-    }
-    println!();
+// Lazily walks the part 1 paths in depth-first order; each stack entry holds a
+// cave on the current path and the index of its next connection to explore
+struct PathsIter<'a> {
+    cave_system: &'a Graph,
+    stack: Vec<(usize, usize)>,
+    visits: Vec<u8>,
 }
 
-fn parse_input(input: &str) -> Rc<RefCell<Cave>> {
-    let lines = input.split_terminator("\n").collect::<Vec<&str>>();
+impl Iterator for PathsIter<'_> {
+    type Item = Vec<String>;
 
-    let mut caves: HashMap<&str, Rc<RefCell<Cave>>> = HashMap::new();
-    for line in lines {
-        let cave_ids: Vec<_> = line.split("-").collect();
-        let id_1 = cave_ids[0];
-        let id_2 = cave_ids[1];
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (cave, next_connection) = self.stack.last_mut()?;
+            let cave = *cave;
+
+            let connections = &self.cave_system.connections[cave];
+            if *next_connection == connections.len() {
+                self.stack.pop();
+                self.visits[cave] -= 1;
+                continue;
+            }
+
+            let c = connections[*next_connection];
+            *next_connection += 1;
+
+            let ctype = &self.cave_system.caves[c].ctype;
+            if *ctype == CaveType::End {
+                let mut path: Vec<String> = self
+                    .stack
+                    .iter()
+                    .map(|&(cave, _)| self.cave_system.caves[cave].id.clone())
+                    .collect();
+                path.push(self.cave_system.caves[c].id.clone());
+                return Some(path);
+            }
 
-        if let None = caves.get(&id_1) {
-            caves.insert(id_1, Cave::new(id_1));
+            if self.visits[c] == 0 || *ctype == CaveType::Big {
+                self.visits[c] += 1;
+                self.stack.push((c, 0));
+            }
         }
+    }
+}
+
+pub fn paths_iter(cave_system: &Graph) -> impl Iterator<Item = Vec<String>> + '_ {
+    let mut visits = vec![0u8; cave_system.caves.len()];
+    visits[cave_system.start] = 1;
+    PathsIter {
+        cave_system,
+        stack: vec![(cave_system.start, 0)],
+        visits,
+    }
+}
 
-        if let None = caves.get(&id_2) {
-            caves.insert(id_2, Cave::new(id_2));
+// Number of part 1 paths going through each cave, counting each path once
+// regardless of how many times it visits the cave
+pub fn cave_visit_counts(cave_system: &Graph) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = cave_system
+        .caves
+        .iter()
+        .map(|cave| (cave.id.clone(), 0))
+        .collect();
+
+    for path in part_1::get_cave_paths(cave_system) {
+        let mut visited = vec![false; cave_system.caves.len()];
+        for cave in path {
+            if !visited[cave] {
+                visited[cave] = true;
+                *counts.get_mut(&cave_system.caves[cave].id).unwrap() += 1;
+            }
         }
+    }
 
-        let cave_1 = caves.get(&id_1).unwrap();
-        let cave_2 = caves.get(&id_2).unwrap();
+    counts
+}
 
-        cave_1.borrow_mut().connections.push(Rc::clone(&cave_2));
-        cave_2.borrow_mut().connections.push(Rc::clone(&cave_1));
+// Minimum, maximum and mean number of caves across all part 1 paths
+pub fn path_length_stats(cave_system: &Graph) -> (usize, usize, f64) {
+    let paths = part_1::get_cave_paths(cave_system);
+    if paths.is_empty() {
+        return (0, 0, 0.0);
     }
 
-    Rc::clone(caves.get("start").unwrap())
+    let lengths: Vec<usize> = paths.iter().map(Vec::len).collect();
+    let min = *lengths.iter().min().unwrap();
+    let max = *lengths.iter().max().unwrap();
+    let mean = lengths.iter().sum::<usize>() as f64 / lengths.len() as f64;
+
+    (min, max, mean)
 }
 
-mod part_1 {
-    use super::*;
+#[derive(Debug, PartialEq)]
+pub enum GraphParsingError {
+    MalformedEdge(String),
+    MissingStart,
+    MissingEnd,
+    StartSelfLoop,
+}
 
-    fn can_be_visited(cave: Rc<RefCell<Cave>>, visited: &CaveList) -> bool {
-        if !visited.contains(&cave) {
-            return true;
+impl Display for GraphParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MalformedEdge(line) => write!(f, "Malformed edge: {line:?}"),
+            Self::MissingStart => write!(f, "Cave system has no start cave"),
+            Self::MissingEnd => write!(f, "Cave system has no end cave"),
+            Self::StartSelfLoop => write!(f, "Start cave is connected to itself"),
         }
-        if cave.borrow().ctype == CaveType::Big {
-            return true;
+    }
+}
+
+pub fn build_graph(edges: &[(&str, &str)]) -> Result<Graph, GraphParsingError> {
+    let mut indices: HashMap<&str, usize> = HashMap::new();
+    let mut caves: Vec<Cave> = Vec::new();
+    let mut connections: Vec<Vec<usize>> = Vec::new();
+    for &(id_1, id_2) in edges {
+        if id_1 == "start" && id_2 == "start" {
+            return Err(GraphParsingError::StartSelfLoop);
+        }
+
+        let mut edge = [0usize; 2];
+        for (i, id) in [id_1, id_2].into_iter().enumerate() {
+            edge[i] = *indices.entry(id).or_insert_with(|| {
+                caves.push(Cave::new(id));
+                connections.push(Vec::new());
+                caves.len() - 1
+            });
+        }
+
+        connections[edge[0]].push(edge[1]);
+        connections[edge[1]].push(edge[0]);
+    }
+
+    let start = *indices
+        .get("start")
+        .ok_or(GraphParsingError::MissingStart)?;
+    if !indices.contains_key("end") {
+        return Err(GraphParsingError::MissingEnd);
+    }
+
+    Ok(Graph {
+        caves,
+        connections,
+        start,
+    })
+}
+
+fn parse_input(input: &str) -> Result<Graph, GraphParsingError> {
+    let lines = input.split_terminator("\n").collect::<Vec<&str>>();
+
+    let mut edges: Vec<(&str, &str)> = Vec::new();
+    for line in lines {
+        match line.split_once("-") {
+            Some((id_1, id_2)) if !id_1.is_empty() && !id_2.is_empty() && !id_2.contains("-") => {
+                edges.push((id_1, id_2))
+            }
+            _ => return Err(GraphParsingError::MalformedEdge(line.to_string())),
         }
-        return false;
+    }
+
+    build_graph(&edges)
+}
+
+pub mod part_1 {
+    use super::*;
+
+    fn can_be_visited(cave_system: &Graph, cave: usize, visits: &[u8]) -> bool {
+        visits[cave] == 0 || cave_system.caves[cave].ctype == CaveType::Big
     }
 
     pub fn visit_cave(
-        cave: Rc<RefCell<Cave>>,
-        mut visited: CaveList,
-        mut curr_path: CaveList,
+        cave_system: &Graph,
+        cave: usize,
+        visits: &mut Vec<u8>,
+        curr_path: &mut CaveList,
         paths: &mut Vec<CaveList>,
     ) {
-        if cave.borrow().ctype == CaveType::End {
-            curr_path.push(Rc::clone(&cave));
+        curr_path.push(cave);
+
+        if cave_system.caves[cave].ctype == CaveType::End {
             paths.push(curr_path.clone());
-            // print!("===> ");
-            // print_cave_list(&curr_path);
+            curr_path.pop();
             return;
         }
 
-        if !visited.contains(&cave) {
-            visited.push(Rc::clone(&cave));
-        }
+        visits[cave] += 1;
 
-        curr_path.push(Rc::clone(&cave));
-
-        for c in cave.borrow().connections.iter() {
-            if !can_be_visited(Rc::clone(c), &visited) {
+        for &c in cave_system.connections[cave].iter() {
+            if !can_be_visited(cave_system, c, visits) {
                 continue;
             }
-            visit_cave(Rc::clone(c), visited.clone(), curr_path.clone(), paths)
+            visit_cave(cave_system, c, visits, curr_path, paths)
         }
+
+        visits[cave] -= 1;
+        curr_path.pop();
     }
 
-    pub fn get_cave_paths(cave_system: Rc<RefCell<Cave>>) -> Vec<CaveList> {
-        let visited: CaveList = Vec::new();
-        let curr_path: CaveList = Vec::new();
+    pub fn get_cave_paths(cave_system: &Graph) -> Vec<CaveList> {
+        let mut visits = vec![0u8; cave_system.caves.len()];
+        let mut curr_path: CaveList = Vec::new();
         let mut paths: Vec<CaveList> = Vec::new();
-        visit_cave(cave_system, visited, curr_path, &mut paths);
+        visit_cave(
+            cave_system,
+            cave_system.start,
+            &mut visits,
+            &mut curr_path,
+            &mut paths,
+        );
         paths
     }
 
-    pub fn count_paths(cave_system: &Rc<RefCell<Cave>>) -> usize {
-        let paths = get_cave_paths(Rc::clone(cave_system));
+    pub fn count_paths(cave_system: &Graph) -> usize {
+        let paths = get_cave_paths(cave_system);
         paths.len()
     }
+
+    fn count_paths_from(cave_system: &Graph, cave: usize, visits: &mut Vec<u8>) -> usize {
+        if cave_system.caves[cave].ctype == CaveType::End {
+            return 1;
+        }
+
+        visits[cave] += 1;
+
+        let mut path_count = 0;
+        for &c in cave_system.connections[cave].iter() {
+            if can_be_visited(cave_system, c, visits) {
+                path_count += count_paths_from(cave_system, c, visits);
+            }
+        }
+
+        visits[cave] -= 1;
+        path_count
+    }
+
+    pub fn count_paths_only(cave_system: &Graph) -> usize {
+        let mut visits = vec![0u8; cave_system.caves.len()];
+        count_paths_from(cave_system, cave_system.start, &mut visits)
+    }
 }
 
-mod part_2 {
+pub mod part_2 {
     use super::*;
 
     fn can_be_visited(
-        cave: Rc<RefCell<Cave>>,
-        visited: &CaveList,
-        twiced_small: &Option<Rc<RefCell<Cave>>>,
+        cave_system: &Graph,
+        cave: usize,
+        visits: &[u8],
+        twiced_small: Option<usize>,
     ) -> bool {
-        if !visited.contains(&cave) {
-            return true;
+        if cave == cave_system.start {
+            return false;
         }
-        if cave.borrow().ctype == CaveType::Big {
+        if visits[cave] == 0 {
             return true;
         }
-        if cave.borrow().ctype == CaveType::Small {
-            if *twiced_small != None {
-                return false;
-            }
-            return true;
+        match cave_system.caves[cave].ctype {
+            CaveType::Big => true,
+            CaveType::Small => twiced_small.is_none() && can_be_twiced(cave_system, cave),
+            _ => false,
         }
-        return false;
+    }
+
+    // Start and end must never be the small cave visited twice, regardless
+    // of how they've been classified
+    fn can_be_twiced(cave_system: &Graph, cave: usize) -> bool {
+        cave != cave_system.start
+            && cave_system.caves[cave].id != "end"
+            && cave_system.caves[cave].ctype == CaveType::Small
     }
 
     pub fn visit_cave(
-        cave: Rc<RefCell<Cave>>,
-        mut visited: CaveList,
-        mut twiced_small: Option<Rc<RefCell<Cave>>>,
-        mut curr_path: CaveList,
+        cave_system: &Graph,
+        cave: usize,
+        visits: &mut Vec<u8>,
+        mut twiced_small: Option<usize>,
+        curr_path: &mut CaveList,
         paths: &mut Vec<CaveList>,
     ) {
-        if cave.borrow().ctype == CaveType::End {
-            curr_path.push(Rc::clone(&cave));
+        curr_path.push(cave);
+
+        if cave_system.caves[cave].ctype == CaveType::End {
             paths.push(curr_path.clone());
-            // print!("===> ");
-            // print_cave_list(&curr_path);
+            curr_path.pop();
             return;
         }
 
-        if !visited.contains(&cave) {
-            visited.push(Rc::clone(&cave));
-        } else if cave.borrow().ctype == CaveType::Small {
-            if twiced_small == None {
-                twiced_small = Some(Rc::clone(&cave));
-            }
+        if visits[cave] > 0 && can_be_twiced(cave_system, cave) {
+            twiced_small = Some(cave);
         }
+        visits[cave] += 1;
 
-        curr_path.push(Rc::clone(&cave));
-
-        for c in cave.borrow().connections.iter() {
-            if !can_be_visited(Rc::clone(c), &visited, &twiced_small) {
+        for &c in cave_system.connections[cave].iter() {
+            if !can_be_visited(cave_system, c, visits, twiced_small) {
                 continue;
             }
-            visit_cave(
-                Rc::clone(c),
-                visited.clone(),
-                twiced_small.clone(),
-                curr_path.clone(),
-                paths,
-            )
+            visit_cave(cave_system, c, visits, twiced_small, curr_path, paths)
         }
+
+        visits[cave] -= 1;
+        curr_path.pop();
     }
 
-    pub fn get_cave_paths(cave_system: Rc<RefCell<Cave>>) -> Vec<CaveList> {
-        let visited: CaveList = Vec::new();
-        let twiced_small: Option<Rc<RefCell<Cave>>> = None;
-        let curr_path: CaveList = Vec::new();
+    pub fn get_cave_paths(cave_system: &Graph) -> Vec<CaveList> {
+        let mut visits = vec![0u8; cave_system.caves.len()];
+        let twiced_small: Option<usize> = None;
+        let mut curr_path: CaveList = Vec::new();
         let mut paths: Vec<CaveList> = Vec::new();
-        visit_cave(cave_system, visited, twiced_small, curr_path, &mut paths);
+        visit_cave(
+            cave_system,
+            cave_system.start,
+            &mut visits,
+            twiced_small,
+            &mut curr_path,
+            &mut paths,
+        );
         paths
     }
 
-    pub fn count_paths(cave_system: &Rc<RefCell<Cave>>) -> usize {
-        let paths = get_cave_paths(Rc::clone(cave_system));
+    pub fn count_paths(cave_system: &Graph) -> usize {
+        let paths = get_cave_paths(cave_system);
         paths.len()
     }
+
+    fn count_paths_from(
+        cave_system: &Graph,
+        cave: usize,
+        visits: &mut Vec<u8>,
+        mut twiced_small: Option<usize>,
+    ) -> usize {
+        if cave_system.caves[cave].ctype == CaveType::End {
+            return 1;
+        }
+
+        if visits[cave] > 0 && can_be_twiced(cave_system, cave) {
+            twiced_small = Some(cave);
+        }
+        visits[cave] += 1;
+
+        let mut path_count = 0;
+        for &c in cave_system.connections[cave].iter() {
+            if can_be_visited(cave_system, c, visits, twiced_small) {
+                path_count += count_paths_from(cave_system, c, visits, twiced_small);
+            }
+        }
+
+        visits[cave] -= 1;
+        path_count
+    }
+
+    pub fn count_paths_only(cave_system: &Graph) -> usize {
+        let mut visits = vec![0u8; cave_system.caves.len()];
+        count_paths_from(cave_system, cave_system.start, &mut visits, None)
+    }
+}
+
+fn can_be_visited_with_budget(
+    cave_system: &Graph,
+    cave: usize,
+    visits: &[usize],
+    k: usize,
+    revisited_small: Option<usize>,
+) -> bool {
+    if cave == cave_system.start {
+        return false;
+    }
+    if visits[cave] == 0 {
+        return true;
+    }
+    match cave_system.caves[cave].ctype {
+        CaveType::Big => true,
+        CaveType::Small => {
+            visits[cave] < k && (revisited_small.is_none() || revisited_small == Some(cave))
+        }
+        _ => false,
+    }
+}
+
+fn count_paths_with_budget_from(
+    cave_system: &Graph,
+    cave: usize,
+    visits: &mut Vec<usize>,
+    k: usize,
+    mut revisited_small: Option<usize>,
+) -> usize {
+    if cave_system.caves[cave].ctype == CaveType::End {
+        return 1;
+    }
+
+    if visits[cave] > 0 && cave_system.caves[cave].ctype == CaveType::Small {
+        revisited_small = Some(cave);
+    }
+    visits[cave] += 1;
+
+    let mut path_count = 0;
+    for &c in cave_system.connections[cave].iter() {
+        if can_be_visited_with_budget(cave_system, c, visits, k, revisited_small) {
+            path_count += count_paths_with_budget_from(cave_system, c, visits, k, revisited_small);
+        }
+    }
+
+    visits[cave] -= 1;
+    path_count
+}
+
+// Counts paths where a single small cave may be visited up to k times and the
+// other small caves at most once; k = 1 gives part 1 and k = 2 gives part 2
+pub fn count_paths_with_budget(cave_system: &Graph, k: usize) -> usize {
+    let mut visits = vec![0usize; cave_system.caves.len()];
+    count_paths_with_budget_from(cave_system, cave_system.start, &mut visits, k, None)
 }
 
 fn main() {
-    let cave_system = parse_input(&read_to_string("data/day-12.txt").unwrap());
+    let cave_system = match parse_input(&read_to_string("data/day-12.txt").unwrap()) {
+        Ok(cave_system) => cave_system,
+        Err(e) => panic!("Error parsing input file for day 12: {}", e),
+    };
 
     println!("== PART 1");
-    let path_count = part_1::count_paths(&cave_system);
+    let path_count = part_1::count_paths_only(&cave_system);
     println!(
         "Number of paths visiting small caves at most once: {}",
         path_count
     );
     println!();
     println!("== PART 2");
-    let path_count = part_2::count_paths(&cave_system);
+    let path_count = part_2::count_paths_only(&cave_system);
     println!(
         "Number of paths visiting one small cave twice and the others only once: {}",
         path_count
@@ -271,52 +557,274 @@ b-end";
 
     #[test]
     fn cave_system_should_start_with_start_cave() {
-        let cave_system = parse_input(INPUT);
-        assert_eq!(cave_system.borrow().ctype, CaveType::Start);
+        let cave_system = parse_input(INPUT).unwrap();
+        assert_eq!(cave_system.caves[cave_system.start].ctype, CaveType::Start);
     }
 
     #[test]
     fn start_node_should_have_big_a_and_small_b_as_children() {
-        let cave_system = parse_input(INPUT);
-        let children = &cave_system.borrow().connections;
+        let cave_system = parse_input(INPUT).unwrap();
+        let children = &cave_system.connections[cave_system.start];
         assert_eq!(children.len(), 2);
 
-        let child_a = Rc::clone(&children[0]);
-        assert_eq!(child_a.borrow().ctype, CaveType::Big);
-        assert_eq!(child_a.borrow().id, "A");
+        let child_a = &cave_system.caves[children[0]];
+        assert_eq!(child_a.ctype, CaveType::Big);
+        assert_eq!(child_a.id, "A");
 
-        let child_b = Rc::clone(&children[1]);
-        assert_eq!(child_b.borrow().ctype, CaveType::Small);
-        assert_eq!(child_b.borrow().id, "b");
+        let child_b = &cave_system.caves[children[1]];
+        assert_eq!(child_b.ctype, CaveType::Small);
+        assert_eq!(child_b.id, "b");
+    }
+
+    #[test]
+    fn caves_should_be_interned_once() {
+        let cave_system = parse_input(INPUT).unwrap();
+        assert_eq!(cave_system.caves.len(), 6);
+        assert_eq!(
+            cave_system.connections.iter().map(Vec::len).sum::<usize>(),
+            14
+        );
     }
 
     #[test]
-    fn visited_contains_using_rc_refcell() {
-        let mut visited: CaveList = Vec::new();
-        let n_start = Cave::new("start");
-        let n_a = Cave::new("A");
-        assert_eq!(visited.contains(&Rc::clone(&n_start)), false);
-        visited.push(Rc::clone(&n_start));
-        visited.push(Rc::clone(&n_a));
-        assert_eq!(visited.contains(&Rc::clone(&n_start)), true);
-        assert_eq!(visited.contains(&Rc::clone(&n_a)), true);
+    fn cave_system_without_end_cave() {
+        let result = parse_input("start-A\nA-b");
+        assert_eq!(result.unwrap_err(), GraphParsingError::MissingEnd);
+    }
+
+    #[test]
+    fn cave_system_without_start_cave() {
+        let result = parse_input("A-b\nA-end");
+        assert_eq!(result.unwrap_err(), GraphParsingError::MissingStart);
+    }
+
+    #[test]
+    fn cave_system_with_start_connected_to_itself() {
+        let result = parse_input("start-start\nstart-end");
+        assert_eq!(result.unwrap_err(), GraphParsingError::StartSelfLoop);
+    }
+
+    #[test]
+    fn cave_system_with_malformed_edge() {
+        let result = parse_input("start-A\nA=end");
+        assert_eq!(
+            result.unwrap_err(),
+            GraphParsingError::MalformedEdge(String::from("A=end"))
+        );
     }
 
     #[test]
     fn cave_system_with_just_start_and_end() {
-        let cave_system = parse_input("start-end");
-        Cave::print(Rc::clone(&cave_system));
-        let paths = part_1::get_cave_paths(Rc::clone(&cave_system));
-        // println!("PATHS");
-        // print_cave_list(&paths[0]);
+        let cave_system = parse_input("start-end").unwrap();
+        let paths = part_1::get_cave_paths(&cave_system);
         assert_eq!(paths.len(), 1);
         assert_eq!(paths[0].len(), 2);
     }
 
     #[test]
     fn cave_system_from_the_example() {
-        let cave_system = parse_input(INPUT);
-        let paths = part_1::get_cave_paths(Rc::clone(&cave_system));
+        let cave_system = parse_input(INPUT).unwrap();
+        let paths = part_1::get_cave_paths(&cave_system);
         assert_eq!(paths.len(), 10);
     }
+
+    #[test]
+    fn path_counts_from_the_example() {
+        let cave_system = parse_input(INPUT).unwrap();
+        assert_eq!(part_1::count_paths(&cave_system), 10);
+        assert_eq!(part_2::count_paths(&cave_system), 36);
+    }
+
+    #[test]
+    fn path_strings_from_the_example() {
+        let cave_system = parse_input(INPUT).unwrap();
+        let mut paths = path_strings(&cave_system);
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "start,A,b,A,c,A,end",
+                "start,A,b,A,end",
+                "start,A,b,end",
+                "start,A,c,A,b,A,end",
+                "start,A,c,A,b,end",
+                "start,A,c,A,end",
+                "start,A,end",
+                "start,b,A,c,A,end",
+                "start,b,A,end",
+                "start,b,end",
+            ]
+        );
+    }
+
+    #[test]
+    fn counting_paths_without_materializing_them() {
+        const LARGER_INPUT: &str = "dc-end
+HN-start
+start-kj
+dc-start
+dc-HN
+LN-dc
+HN-end
+kj-sa
+kj-HN
+kj-dc";
+        const EVEN_LARGER_INPUT: &str = "fs-end
+he-DX
+fs-he
+start-DX
+pj-DX
+end-zg
+zg-sl
+zg-pj
+pj-he
+RW-he
+fs-DX
+pj-RW
+zg-RW
+start-pj
+he-WI
+zg-he
+pj-fs
+start-RW";
+
+        for (input, part_1_count, part_2_count) in [
+            (INPUT, 10, 36),
+            (LARGER_INPUT, 19, 103),
+            (EVEN_LARGER_INPUT, 226, 3509),
+        ] {
+            let cave_system = parse_input(input).unwrap();
+            assert_eq!(part_1::count_paths_only(&cave_system), part_1_count);
+            assert_eq!(part_1::count_paths(&cave_system), part_1_count);
+            assert_eq!(part_2::count_paths_only(&cave_system), part_2_count);
+            assert_eq!(part_2::count_paths(&cave_system), part_2_count);
+        }
+    }
+
+    #[test]
+    fn start_cave_should_never_be_revisited() {
+        // start-A, A-end, plus a start-start self-loop and start misclassified
+        // as a small cave, which the parser would never produce
+        let mut start = Cave::new("start");
+        start.ctype = CaveType::Small;
+        let cave_system = Graph {
+            caves: vec![start, Cave::new("A"), Cave::new("end")],
+            connections: vec![vec![0, 1], vec![0, 2], vec![1]],
+            start: 0,
+        };
+        assert_eq!(part_2::count_paths(&cave_system), 1);
+        assert_eq!(part_2::count_paths_only(&cave_system), 1);
+    }
+
+    #[test]
+    fn path_length_stats_from_the_example() {
+        let cave_system = parse_input(INPUT).unwrap();
+        let (min, max, mean) = path_length_stats(&cave_system);
+        // start,A,end and start,b,end are the shortest; start,A,b,A,c,A,end
+        // and start,A,c,A,b,A,end the longest
+        assert_eq!((min, max), (3, 7));
+        assert_eq!(mean, 5.0);
+        assert!(min as f64 <= mean && mean <= max as f64);
+    }
+
+    #[test]
+    fn path_length_stats_with_just_start_and_end() {
+        let cave_system = parse_input("start-end").unwrap();
+        assert_eq!(path_length_stats(&cave_system), (2, 2, 2.0));
+    }
+
+    #[test]
+    fn building_the_example_graph_from_edges() {
+        let cave_system = build_graph(&[
+            ("start", "A"),
+            ("start", "b"),
+            ("A", "c"),
+            ("A", "b"),
+            ("b", "d"),
+            ("A", "end"),
+            ("b", "end"),
+        ])
+        .unwrap();
+        let parsed_cave_system = parse_input(INPUT).unwrap();
+        assert_eq!(
+            part_1::count_paths(&cave_system),
+            part_1::count_paths(&parsed_cave_system)
+        );
+        assert_eq!(part_1::count_paths(&cave_system), 10);
+    }
+
+    #[test]
+    fn cave_types_by_id() {
+        let cave_system = parse_input(INPUT).unwrap();
+        assert_eq!(cave_type(&cave_system, "A"), Some(CaveType::Big));
+        assert_eq!(cave_type(&cave_system, "b"), Some(CaveType::Small));
+        assert_eq!(cave_type(&cave_system, "start"), Some(CaveType::Start));
+        assert_eq!(cave_type(&cave_system, "end"), Some(CaveType::End));
+        assert_eq!(cave_type(&cave_system, "X"), None);
+    }
+
+    #[test]
+    fn dot_export_of_the_example() {
+        let cave_system = parse_input(INPUT).unwrap();
+        let dot = to_dot(&cave_system);
+        assert!(dot.starts_with("graph caves {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("\"start\" [shape=doublecircle];"));
+        assert!(dot.contains("\"end\" [shape=doublecircle];"));
+        assert!(dot.contains("\"A\" [shape=ellipse];"));
+        assert!(dot.contains("\"b\" [shape=box];"));
+        assert!(dot.contains("\"start\" -- \"A\";"));
+        assert!(dot.contains("\"b\" -- \"d\";"));
+        assert!(!dot.contains("\"A\" -- \"start\";"));
+        assert_eq!(dot.matches(" -- ").count(), 7);
+    }
+
+    #[test]
+    fn lazily_taking_the_first_paths_of_the_example() {
+        let cave_system = parse_input(INPUT).unwrap();
+        let paths: Vec<Vec<String>> = paths_iter(&cave_system).take(3).collect();
+        assert_eq!(
+            paths,
+            vec![
+                vec!["start", "A", "c", "A", "b", "A", "end"],
+                vec!["start", "A", "c", "A", "b", "end"],
+                vec!["start", "A", "c", "A", "end"],
+            ]
+        );
+    }
+
+    #[test]
+    fn iterating_over_all_paths_of_the_example() {
+        let cave_system = parse_input(INPUT).unwrap();
+        let paths: Vec<String> = paths_iter(&cave_system).map(|p| p.join(",")).collect();
+        assert_eq!(paths, path_strings(&cave_system));
+    }
+
+    #[test]
+    fn counting_paths_with_a_small_cave_visit_budget() {
+        let cave_system = parse_input(INPUT).unwrap();
+        assert_eq!(count_paths_with_budget(&cave_system, 1), 10);
+        assert_eq!(count_paths_with_budget(&cave_system, 2), 36);
+        assert!(count_paths_with_budget(&cave_system, 3) > 36);
+    }
+
+    #[test]
+    fn cave_visit_counts_of_the_example() {
+        let cave_system = parse_input(INPUT).unwrap();
+        let counts = cave_visit_counts(&cave_system);
+        assert_eq!(counts["start"], 10);
+        assert_eq!(counts["end"], 10);
+        assert_eq!(counts["A"], 9);
+        assert_eq!(counts["b"], 8);
+        assert_eq!(counts["c"], 5);
+        assert_eq!(counts["d"], 0);
+    }
+
+    #[test]
+    fn dot_export_emits_a_self_loop_once() {
+        let cave_system = build_graph(&[("start", "A"), ("A", "A"), ("A", "end")]).unwrap();
+        let dot = to_dot(&cave_system);
+        assert_eq!(dot.matches("\"A\" -- \"A\";").count(), 1);
+        assert_eq!(dot.matches(" -- ").count(), 3);
+    }
 }
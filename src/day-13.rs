@@ -1,5 +1,5 @@
 use std::collections::HashSet;
-use std::convert::Infallible;
+use std::fmt::Display;
 use std::fs::read_to_string;
 use std::str::FromStr;
 
@@ -12,57 +12,113 @@ pub enum Fold {
     Y(u32),
 }
 
+#[derive(Debug, PartialEq)]
+pub enum InputParsingError {
+    MalformedPoint(String),
+    MalformedFold(String),
+}
+
+impl Display for InputParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MalformedPoint(s) => write!(f, "Malformed point: {s:?}"),
+            Self::MalformedFold(s) => write!(f, "Malformed fold: {s:?}"),
+        }
+    }
+}
+
 impl FromStr for Point {
-    type Err = Infallible;
+    type Err = InputParsingError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coords: Vec<u32> = s.split(",").map(|v| v.parse::<u32>().unwrap()).collect();
-        Ok(Point(coords[0], coords[1]))
+        let malformed = || InputParsingError::MalformedPoint(s.to_string());
+        let (x, y) = s.split_once(",").ok_or_else(malformed)?;
+        let x = x.trim().parse::<u32>().map_err(|_| malformed())?;
+        let y = y.trim().parse::<u32>().map_err(|_| malformed())?;
+        Ok(Point(x, y))
     }
 }
 
 impl FromStr for Fold {
-    type Err = Infallible;
+    type Err = InputParsingError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let data = &s["fold along ".len()..];
-        let parts: Vec<&str> = data.split("=").collect();
-        if parts[0] == "x" {
-            return Ok(Fold::X(parts[1].parse::<u32>().unwrap()));
+        let malformed = || InputParsingError::MalformedFold(s.to_string());
+        let data = s.strip_prefix("fold along ").ok_or_else(malformed)?;
+        let (axis, value) = data.split_once("=").ok_or_else(malformed)?;
+        let value = value.trim().parse::<u32>().map_err(|_| malformed())?;
+        match axis {
+            "x" => Ok(Fold::X(value)),
+            "y" => Ok(Fold::Y(value)),
+            _ => Err(malformed()),
         }
-        Ok(Fold::Y(parts[1].parse::<u32>().unwrap()))
     }
 }
 
-fn parse_input(input: &str) -> (Vec<Point>, Vec<Fold>) {
+fn parse_input(input: &str) -> Result<(Vec<Point>, Vec<Fold>), InputParsingError> {
     let mut points: Vec<Point> = Vec::new();
     let mut folds: Vec<Fold> = Vec::new();
 
     let lines: Vec<&str> = input.split_terminator("\n").collect();
 
+    // Points and folds are told apart line by line, so they may come in any
+    // order; each keeps the relative order in which it appeared
     for line in lines {
-        if line.trim().is_empty() {
+        let line = line.trim();
+        if line.is_empty() {
             continue;
         }
         if line.starts_with("fold") {
-            folds.push(Fold::from_str(line).unwrap());
+            folds.push(Fold::from_str(line)?);
         } else {
-            points.push(Point::from_str(line).unwrap());
+            points.push(Point::from_str(line)?);
         }
     }
 
-    (points, folds)
+    Ok((points, folds))
+}
+
+// Folding a point farther from the fold line than the line is from the edge
+// would take it to a negative coordinate
+fn fold_on_x(p: &Point, x: u32) -> Result<Point, FoldError> {
+    match (x * 2).checked_sub(p.0) {
+        Some(folded_x) => Ok(Point(folded_x, p.1)),
+        None => Err(FoldError::NegativeCoordinate(*p)),
+    }
+}
+
+fn fold_on_y(p: &Point, y: u32) -> Result<Point, FoldError> {
+    match (y * 2).checked_sub(p.1) {
+        Some(folded_y) => Ok(Point(p.0, folded_y)),
+        None => Err(FoldError::NegativeCoordinate(*p)),
+    }
 }
 
-fn fold_on_x(p: &Point, x: u32) -> Point {
-    Point(x * 2 - p.0, p.1)
+#[derive(Debug, PartialEq)]
+pub enum FoldError {
+    PointOnFoldLine(Point),
+    NegativeCoordinate(Point),
 }
 
-fn fold_on_y(p: &Point, y: u32) -> Point {
-    Point(p.0, y * 2 - p.1)
+impl Display for FoldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PointOnFoldLine(p) => write!(f, "Point {},{} lies on the fold line", p.0, p.1),
+            Self::NegativeCoordinate(p) => {
+                write!(
+                    f,
+                    "Point {},{} would fold to a negative coordinate",
+                    p.0, p.1
+                )
+            }
+        }
+    }
 }
 
-fn apply_fold(points: &mut [Point], fold: &Fold) -> Vec<Point> {
+// Points on either side of the fold line end up on the upper/left half. The
+// puzzle guarantees no point lies exactly on a fold line, so finding one means
+// the input is malformed and is reported as an error rather than dropped.
+fn apply_fold(points: &[Point], fold: &Fold) -> Result<Vec<Point>, FoldError> {
     let mut point_set: HashSet<Point> = HashSet::new();
     match fold {
         Fold::X(x) => {
@@ -70,7 +126,9 @@ fn apply_fold(points: &mut [Point], fold: &Fold) -> Vec<Point> {
                 if p.0 < *x {
                     point_set.insert(*p);
                 } else if p.0 > *x {
-                    point_set.insert(fold_on_x(&p, *x));
+                    point_set.insert(fold_on_x(p, *x)?);
+                } else {
+                    return Err(FoldError::PointOnFoldLine(*p));
                 }
             }
         }
@@ -79,15 +137,18 @@ fn apply_fold(points: &mut [Point], fold: &Fold) -> Vec<Point> {
                 if p.1 < *y {
                     point_set.insert(*p);
                 } else if p.1 > *y {
-                    point_set.insert(fold_on_y(&p, *y));
+                    point_set.insert(fold_on_y(p, *y)?);
+                } else {
+                    return Err(FoldError::PointOnFoldLine(*p));
                 }
             }
         }
     }
-    point_set.into_iter().collect()
+    Ok(point_set.into_iter().collect())
 }
 
-fn print_points(points: &[Point]) {
+// Maximum x and y coordinates among the points, (0, 0) if there are none
+pub fn bounding_box(points: &[Point]) -> (u32, u32) {
     let mut max_x = 0u32;
     let mut max_y = 0u32;
     for p in points {
@@ -98,66 +159,183 @@ fn print_points(points: &[Point]) {
             max_y = p.1;
         }
     }
+    (max_x, max_y)
+}
+
+fn render_points(points: &[Point]) -> String {
+    let (max_x, max_y) = bounding_box(points);
     let x_size = (max_x + 1) as usize;
     let y_size = (max_y + 1) as usize;
     let mut grid: Vec<Vec<bool>> = vec![vec![false; x_size]; y_size];
     for p in points {
         grid[p.1 as usize][p.0 as usize] = true;
     }
-    for y in 0..y_size {
-        for x in 0..x_size {
-            if grid[y][x] == true {
-                print!("█");
+    render_grid(&grid)
+}
+
+fn render_grid(grid: &[Vec<bool>]) -> String {
+    let mut rendered = String::new();
+    for row in grid {
+        for &dot in row {
+            if dot {
+                rendered.push('█');
             } else {
-                print!(".");
+                rendered.push('.');
             }
         }
-        println!();
+        rendered.push('\n');
+    }
+    rendered
+}
+
+// Folds each point through all folds and stamps it on a grid just big enough
+// for the folded dots, instead of collecting points into a set after every fold
+pub fn apply_folds_grid(
+    mut points: Vec<Point>,
+    folds: &[Fold],
+) -> Result<Vec<Vec<bool>>, FoldError> {
+    for p in points.iter_mut() {
+        for fold in folds {
+            *p = match fold {
+                Fold::X(x) if p.0 > *x => fold_on_x(p, *x)?,
+                Fold::Y(y) if p.1 > *y => fold_on_y(p, *y)?,
+                Fold::X(x) if p.0 == *x => return Err(FoldError::PointOnFoldLine(*p)),
+                Fold::Y(y) if p.1 == *y => return Err(FoldError::PointOnFoldLine(*p)),
+                _ => *p,
+            };
+        }
+    }
+
+    let (max_x, max_y) = bounding_box(&points);
+    let mut grid: Vec<Vec<bool>> = vec![vec![false; (max_x + 1) as usize]; (max_y + 1) as usize];
+    for p in points {
+        grid[p.1 as usize][p.0 as usize] = true;
+    }
+
+    Ok(grid)
+}
+
+fn _print_points(points: &[Point]) {
+    print!("{}", render_points(points));
+}
+
+pub fn dot_counts_per_fold(
+    mut points: Vec<Point>,
+    folds: &[Fold],
+) -> Result<Vec<usize>, FoldError> {
+    let mut dot_counts: Vec<usize> = Vec::new();
+    for fold in folds {
+        points = apply_fold(&points, fold)?;
+        dot_counts.push(points.len());
+    }
+    Ok(dot_counts)
+}
+
+const LETTER_WIDTH: usize = 4;
+const LETTER_HEIGHT: usize = 6;
+const LETTER_SPACING: usize = 1;
+
+// Capital letters of the 4x6 font used by AoC puzzles, row by row
+static LETTER_FONT: [(char, &str); 17] = [
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('I', ".###..#...#...#...#..###"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Z', "####...#..#..#..#...####"),
+];
+
+// Reads the capital letters drawn by the points; unrecognized letters are
+// decoded as '?'
+pub fn decode_letters(points: &[Point]) -> String {
+    if points.is_empty() {
+        return String::new();
+    }
+
+    let x_size = points.iter().map(|p| p.0).max().unwrap() as usize + 1;
+    let letter_count = (x_size + LETTER_SPACING).div_ceil(LETTER_WIDTH + LETTER_SPACING);
+    let grid_width = letter_count * (LETTER_WIDTH + LETTER_SPACING);
+    let mut grid: Vec<Vec<bool>> = vec![vec![false; grid_width]; LETTER_HEIGHT];
+    for p in points {
+        if (p.1 as usize) < LETTER_HEIGHT {
+            grid[p.1 as usize][p.0 as usize] = true;
+        }
     }
+
+    (0..letter_count)
+        .map(|i| {
+            let left = i * (LETTER_WIDTH + LETTER_SPACING);
+            let pattern: String = grid
+                .iter()
+                .flat_map(|row| row[left..left + LETTER_WIDTH].iter())
+                .map(|&dot| if dot { '#' } else { '.' })
+                .collect();
+            LETTER_FONT
+                .iter()
+                .find(|(_, letter_pattern)| *letter_pattern == pattern)
+                .map_or('?', |(letter, _)| *letter)
+        })
+        .collect()
 }
 
 mod part_1 {
     use super::*;
 
-    pub fn dot_count_after_first_fold(points: &mut Vec<Point>, fold: &Fold) -> usize {
-        apply_fold(points, fold).len()
+    pub fn dot_count_after_first_fold(points: &[Point], fold: &Fold) -> Result<usize, FoldError> {
+        Ok(apply_fold(points, fold)?.len())
     }
 }
 
 mod part_2 {
     use super::*;
 
-    pub fn apply_folds(mut points: Vec<Point>, folds: &[Fold]) {
-        let folds_len = folds.len();
-        for f in 0..folds_len {
-            let fold = &folds[f];
-            points = apply_fold(&mut points, fold);
-            if f == folds_len - 1 {
-                print_points(&points);
-            }
+    pub fn apply_folds(mut points: Vec<Point>, folds: &[Fold]) -> Result<Vec<Point>, FoldError> {
+        for fold in folds {
+            points = apply_fold(&points, fold)?;
         }
+        Ok(points)
     }
 }
 
 fn main() {
-    let (mut points, folds) = parse_input(&read_to_string("data/day-13.txt").unwrap());
+    let (points, folds) = match parse_input(&read_to_string("data/day-13.txt").unwrap()) {
+        Ok(input) => input,
+        Err(e) => panic!("Error parsing input file for day 13: {}", e),
+    };
 
-    let mut mx = 0;
-    let mut my = 0;
-    for p in points.iter() {
-        if p.0 > mx { mx = p.0; }
-        if p.1 > my { my = p.1; }
-    }
+    let (mx, my) = bounding_box(&points);
     println!("max x: {mx}");
     println!("max y: {my}");
 
     println!("== PART 1");
-    let dot_count = part_1::dot_count_after_first_fold(&mut points, &folds[0]);
-    println!("Number of visible points after first fold: {dot_count}");
+    match folds.first() {
+        Some(fold) => match part_1::dot_count_after_first_fold(&points, fold) {
+            Ok(dot_count) => println!("Number of visible points after first fold: {dot_count}"),
+            Err(e) => println!("Error applying first fold: {e}"),
+        },
+        None => println!("No folds in the input"),
+    }
 
     println!("== PART 2");
-    println!("Code to activate the infrared thermal imaging camera system:");
-    part_2::apply_folds(points.clone(), &folds);
+    match part_2::apply_folds(points.clone(), &folds) {
+        Ok(points) => {
+            println!("Code to activate the infrared thermal imaging camera system:");
+            print!("{}", render_points(&points));
+            println!("Decoded: {}", decode_letters(&points));
+        }
+        Err(e) => println!("Error applying folds: {e}"),
+    }
 }
 
 #[cfg(test)]
@@ -171,6 +349,28 @@ mod tests {
 fold along x=50
 fold along y=100";
 
+    const EXAMPLE_INPUT: &str = "6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5";
+
     #[test]
     fn points_should_be_comparable() {
         let p_1 = Point(2, 3);
@@ -194,32 +394,233 @@ fold along y=100";
         assert_eq!(f, Fold::X(110));
     }
 
+    #[test]
+    fn point_parsing_without_comma() {
+        assert_eq!(
+            Point::from_str("1"),
+            Err(InputParsingError::MalformedPoint(String::from("1")))
+        );
+    }
+
+    #[test]
+    fn fold_parsing_with_invalid_axis() {
+        assert_eq!(
+            Fold::from_str("fold along z=3"),
+            Err(InputParsingError::MalformedFold(String::from(
+                "fold along z=3"
+            )))
+        );
+    }
+
+    #[test]
+    fn fold_parsing_without_value() {
+        assert_eq!(
+            Fold::from_str("fold along x="),
+            Err(InputParsingError::MalformedFold(String::from(
+                "fold along x="
+            )))
+        );
+    }
+
     #[test]
     fn input_parsing() {
-        let (points, folds) = parse_input(INPUT);
+        let (points, folds) = parse_input(INPUT).unwrap();
         assert_eq!(points.len(), 3);
         assert_eq!(points, vec![Point(1, 2), Point(10, 20), Point(100, 200)]);
         assert_eq!(folds.len(), 2);
         assert_eq!(folds, vec![Fold::X(50), Fold::Y(100)]);
     }
 
+    #[test]
+    fn input_parsing_with_interleaved_points_and_folds() {
+        let input = "fold along y=7
+6,10
+0,14
+fold along x=5
+9,10\r
+
+4,11
+";
+        let (points, folds) = parse_input(input).unwrap();
+        assert_eq!(
+            points,
+            vec![Point(6, 10), Point(0, 14), Point(9, 10), Point(4, 11)]
+        );
+        assert_eq!(folds, vec![Fold::Y(7), Fold::X(5)]);
+    }
+
     #[test]
     fn point_folding_on_x() {
         let p = Point(3, 1);
-        let folded = fold_on_x(&p, 2);
+        let folded = fold_on_x(&p, 2).unwrap();
         assert_eq!(folded, Point(1, 1));
     }
 
     #[test]
     fn point_folding_on_y() {
         let p = Point(1, 2);
-        let folded = fold_on_y(&p, 1);
+        let folded = fold_on_y(&p, 1).unwrap();
         assert_eq!(folded, Point(1, 0));
     }
 
+    #[test]
+    fn folding_to_a_negative_coordinate() {
+        assert_eq!(
+            fold_on_x(&Point(5, 1), 2),
+            Err(FoldError::NegativeCoordinate(Point(5, 1)))
+        );
+        assert_eq!(
+            fold_on_y(&Point(1, 3), 1),
+            Err(FoldError::NegativeCoordinate(Point(1, 3)))
+        );
+        let points = vec![Point(0, 0), Point(1, 1), Point(7, 0)];
+        assert_eq!(
+            apply_fold(&points, &Fold::X(3)),
+            Err(FoldError::NegativeCoordinate(Point(7, 0)))
+        );
+    }
+
     #[test]
     fn printing_points() {
-        let (points, _) = parse_input(INPUT);
-        print_points(&points);
+        let (points, _) = parse_input(INPUT).unwrap();
+        _print_points(&points);
+    }
+
+    #[test]
+    fn folding_the_problem_example() {
+        let (points, folds) = parse_input(EXAMPLE_INPUT).unwrap();
+        let rendered = render_points(&part_2::apply_folds(points, &folds).unwrap());
+        assert_eq!(
+            rendered,
+            "█████
+█...█
+█...█
+█...█
+█████
+"
+        );
+    }
+
+    #[test]
+    fn dot_counts_after_each_fold_of_the_problem_example() {
+        let (points, folds) = parse_input(EXAMPLE_INPUT).unwrap();
+        let dot_counts = dot_counts_per_fold(points.clone(), &folds).unwrap();
+        assert_eq!(dot_counts, vec![17, 16]);
+        assert_eq!(
+            dot_counts[0],
+            part_1::dot_count_after_first_fold(&points, &folds[0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn bounding_box_after_all_folds_of_the_problem_example() {
+        let (mut points, folds) = parse_input(EXAMPLE_INPUT).unwrap();
+        assert_eq!(bounding_box(&points), (10, 14));
+        for fold in folds.iter() {
+            points = apply_fold(&points, fold).unwrap();
+        }
+        assert_eq!(bounding_box(&points), (4, 4));
+        assert_eq!(bounding_box(&[]), (0, 0));
+    }
+
+    #[test]
+    fn final_points_of_the_problem_example() {
+        let (points, folds) = parse_input(EXAMPLE_INPUT).unwrap();
+        let points = part_2::apply_folds(points, &folds).unwrap();
+        assert_eq!(points.len(), 16);
+        assert!(points.iter().all(|p| p.0 <= 4 && p.1 <= 4));
+    }
+
+    #[test]
+    fn folding_onto_a_grid_renders_the_same_pattern() {
+        let (points, folds) = parse_input(EXAMPLE_INPUT).unwrap();
+        let grid = apply_folds_grid(points.clone(), &folds).unwrap();
+        let rendered = render_points(&part_2::apply_folds(points, &folds).unwrap());
+        assert_eq!(grid.len(), 5);
+        assert_eq!(render_grid(&grid), rendered);
+    }
+
+    #[test]
+    fn folding_should_leave_input_points_untouched() {
+        let points = vec![Point(0, 0), Point(4, 1), Point(1, 3)];
+        let folded = apply_fold(&points, &Fold::X(2)).unwrap();
+        assert_eq!(points, vec![Point(0, 0), Point(4, 1), Point(1, 3)]);
+        assert_eq!(folded.len(), 3);
+        assert!(folded.contains(&Point(0, 1)));
+    }
+
+    #[test]
+    fn folding_with_a_point_on_the_fold_line() {
+        let points = vec![Point(0, 0), Point(2, 1), Point(4, 1)];
+        assert_eq!(
+            apply_fold(&points, &Fold::X(2)),
+            Err(FoldError::PointOnFoldLine(Point(2, 1)))
+        );
+        assert_eq!(
+            apply_fold(&points, &Fold::Y(1)),
+            Err(FoldError::PointOnFoldLine(Point(2, 1)))
+        );
+    }
+
+    fn points_from_drawing(drawing: &str) -> Vec<Point> {
+        let mut points: Vec<Point> = Vec::new();
+        for (y, line) in drawing.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if c == '#' {
+                    points.push(Point(x as u32, y as u32));
+                }
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn decoding_letters() {
+        let points = points_from_drawing(
+            "#..#.####.#....#.....##.
+#..#.#....#....#....#..#
+####.###..#....#....#..#
+#..#.#....#....#....#..#
+#..#.#....#....#....#..#
+#..#.####.####.####..##.",
+        );
+        assert_eq!(decode_letters(&points), "HELLO");
+    }
+
+    #[test]
+    fn decoding_unknown_letters() {
+        let points = points_from_drawing(
+            "#...#
+.#.#.
+..#..
+..#..
+..#..
+..#..",
+        );
+        assert_eq!(decode_letters(&points), "??");
+    }
+
+    #[test]
+    fn decoding_a_shape_that_is_not_a_letter() {
+        let points = points_from_drawing(
+            "####
+#..#
+#..#
+#..#
+#..#
+####",
+        );
+        assert_eq!(decode_letters(&points), "?");
+        assert_eq!(decode_letters(&[]), "");
+    }
+
+    #[test]
+    fn folding_onto_a_grid_reports_the_folded_point_on_a_fold_line() {
+        let points = vec![Point(0, 0), Point(6, 1)];
+        let folds = vec![Fold::X(4), Fold::X(2)];
+        assert_eq!(
+            apply_folds_grid(points, &folds),
+            Err(FoldError::PointOnFoldLine(Point(2, 1)))
+        );
     }
 }
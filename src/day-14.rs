@@ -1,28 +1,53 @@
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::fs::read_to_string;
 
 type PolymerTemplate = Vec<char>;
 type Pair = (char, char);
-type InsertionRules = HashMap<Pair, char>;
-type PairCounts = HashMap<Pair, usize>;
-type SymbolCounts = HashMap<char, usize>;
+type InsertionRules = HashMap<Pair, String>;
+type PairCounts = HashMap<Pair, u64>;
+type SymbolCounts = HashMap<char, u64>;
 
-fn parse_input(input: &str) -> (PolymerTemplate, InsertionRules) {
+#[derive(Debug, PartialEq)]
+enum InputParsingError {
+    MissingRules,
+    MalformedRule(String),
+}
+
+impl Display for InputParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingRules => write!(f, "Missing pair insertion rules"),
+            Self::MalformedRule(line) => write!(f, "Malformed pair insertion rule: {line:?}"),
+        }
+    }
+}
+
+fn parse_rule(line: &str) -> Result<(Pair, String), InputParsingError> {
+    let malformed = || InputParsingError::MalformedRule(line.to_string());
+    let (pair, insertion) = line.split_once(" -> ").ok_or_else(malformed)?;
+    let pair: Vec<char> = pair.chars().collect();
+    if pair.len() != 2 || insertion.is_empty() || insertion.contains(' ') {
+        return Err(malformed());
+    }
+    Ok(((pair[0], pair[1]), insertion.to_string()))
+}
+
+fn parse_input(input: &str) -> Result<(PolymerTemplate, InsertionRules), InputParsingError> {
     let sections: Vec<&str> = input.split_terminator("\n\n").collect();
+    if sections.len() < 2 {
+        return Err(InputParsingError::MissingRules);
+    }
     let template: PolymerTemplate = sections[0].chars().collect();
 
     let rule_lines: Vec<&str> = sections[1].split_terminator("\n").collect();
     let mut rules = InsertionRules::new();
     for line in rule_lines {
-        let parts: Vec<&str> = line.split(" -> ").collect();
-        let pair: Pair = (
-            parts[0].chars().next().unwrap(),
-            parts[0].chars().skip(1).next().unwrap(),
-        );
-        rules.insert(pair, parts[1].parse::<char>().unwrap());
+        let (pair, insertion) = parse_rule(line)?;
+        rules.insert(pair, insertion);
     }
 
-    (template, rules)
+    Ok((template, rules))
 }
 
 fn pair_counting_step(
@@ -32,13 +57,25 @@ fn pair_counting_step(
     let mut new_pair_counts = PairCounts::new();
 
     for (pair, count) in pair_counts {
-        let new_symbol: char = *(rules.get(pair).unwrap());
-
-        let new_pair_1: Pair = (pair.0, new_symbol);
-        let new_pair_2: Pair = (new_symbol, pair.1);
+        // Pairs without an insertion rule are left untouched
+        let insertion: &str = match rules.get(pair) {
+            Some(insertion) => insertion,
+            None => {
+                *(new_pair_counts.entry(*pair).or_insert(0)) += count;
+                continue;
+            }
+        };
 
-        *(new_pair_counts.entry(new_pair_1).or_insert(0)) += count;
-        *(new_pair_counts.entry(new_pair_2).or_insert(0)) += count;
+        // The pair's symbols surround the inserted ones, and every two
+        // consecutive symbols of the result form a new pair
+        let symbols: Vec<char> = std::iter::once(pair.0)
+            .chain(insertion.chars())
+            .chain(std::iter::once(pair.1))
+            .collect();
+        for new_pair in symbols.windows(2) {
+            let new_pair: Pair = (new_pair[0], new_pair[1]);
+            *(new_pair_counts.entry(new_pair).or_insert(0)) += count;
+        }
     }
 
     new_pair_counts
@@ -48,14 +85,16 @@ fn create_initial_pair_counts(
     template: &PolymerTemplate
 ) -> PairCounts {
     let mut pair_counts = PairCounts::new();
-    for i in 0..template.len() - 1 {
-        let symbols = &template[i..i + 2];
+    for symbols in template.windows(2) {
         let pair: Pair = (symbols[0], symbols[1]);
         *(pair_counts.entry(pair).or_insert(0)) += 1;
     }
     pair_counts
 }
 
+// Each symbol but the last one starts exactly one pair, so counting the first
+// symbol of every pair plus the template's last symbol (which never changes)
+// counts every symbol once
 fn compute_symbol_counts(
     template: &PolymerTemplate,
     pair_counts: &PairCounts
@@ -63,18 +102,16 @@ fn compute_symbol_counts(
     let mut symbol_counts = SymbolCounts::new();
     for (pair, count) in pair_counts {
         *(symbol_counts.entry(pair.0).or_insert(0)) += count;
-        *(symbol_counts.entry(pair.1).or_insert(0)) += count;
     }
-    let first_template_symbol = template.iter().next().unwrap();
-    let last_template_symbol = template.iter().last().unwrap();
-    *(symbol_counts.entry(*first_template_symbol).or_insert(0)) += 1;
-    *(symbol_counts.entry(*last_template_symbol).or_insert(0)) += 1;
+    if let Some(last_template_symbol) = template.last() {
+        *(symbol_counts.entry(*last_template_symbol).or_insert(0)) += 1;
+    }
     symbol_counts
 }
 
-fn min_and_max_symbol_occurrences(symbol_counts: &SymbolCounts) -> (usize, usize) {
-    let mut min: usize = usize::MAX;
-    let mut max = usize::MIN;
+fn min_and_max_symbol_occurrences(symbol_counts: &SymbolCounts) -> (u64, u64) {
+    let mut min: u64 = u64::MAX;
+    let mut max = u64::MIN;
     for (_, &count) in symbol_counts {
         if count < min {
             min = count;
@@ -86,53 +123,180 @@ fn min_and_max_symbol_occurrences(symbol_counts: &SymbolCounts) -> (usize, usize
     (min, max)
 }
 
-fn most_common_minus_least_common_after_n_steps(
+const MAX_GROW_POLYMER_STEPS: u32 = 20;
+
+// Actually builds the polymer, which roughly doubles in length at every step,
+// so it's only meant for a small number of steps (at most 20)
+pub fn grow_polymer(template: &PolymerTemplate, rules: &InsertionRules, steps: u32) -> String {
+    assert!(
+        steps <= MAX_GROW_POLYMER_STEPS,
+        "Growing a polymer for more than {MAX_GROW_POLYMER_STEPS} steps is not supported"
+    );
+
+    let mut polymer: Vec<char> = template.clone();
+    for _ in 0..steps {
+        let mut new_polymer: Vec<char> = Vec::with_capacity(polymer.len() * 2);
+        for (i, &symbol) in polymer.iter().enumerate() {
+            new_polymer.push(symbol);
+            if let Some(&next_symbol) = polymer.get(i + 1) {
+                if let Some(insertion) = rules.get(&(symbol, next_symbol)) {
+                    new_polymer.extend(insertion.chars());
+                }
+            }
+        }
+        polymer = new_polymer;
+    }
+
+    polymer.into_iter().collect()
+}
+
+// Pair counts of the initial template followed by those after each step
+pub fn pair_counts_per_step(
+    template: &PolymerTemplate,
+    rules: &InsertionRules,
+    steps: u32,
+) -> Vec<PairCounts> {
+    let mut pair_counts_per_step = vec![create_initial_pair_counts(template)];
+    for _ in 0..steps {
+        let pair_counts = pair_counting_step(rules, pair_counts_per_step.last().unwrap());
+        pair_counts_per_step.push(pair_counts);
+    }
+    pair_counts_per_step
+}
+
+pub fn symbol_counts_after_n_steps(
     template: &PolymerTemplate,
     rules: &InsertionRules,
     steps: u32,
-) -> usize {
+) -> SymbolCounts {
     let mut pair_counts = create_initial_pair_counts(template);
     for _ in 0..steps {
         pair_counts = pair_counting_step(rules, &pair_counts);
     }
+    compute_symbol_counts(template, &pair_counts)
+}
+
+// Same counting as `compute_symbol_counts`, but only for `symbol`
+pub fn count_of(
+    template: &PolymerTemplate,
+    rules: &InsertionRules,
+    steps: u32,
+    symbol: char,
+) -> u64 {
+    let mut pair_counts = create_initial_pair_counts(template);
+    for _ in 0..steps {
+        pair_counts = pair_counting_step(rules, &pair_counts);
+    }
+    let pair_starts: u64 = pair_counts
+        .iter()
+        .filter(|(pair, _)| pair.0 == symbol)
+        .map(|(_, count)| count)
+        .sum();
+    pair_starts + u64::from(template.last() == Some(&symbol))
+}
+
+fn is_single_most_common(symbol_counts: &SymbolCounts, target: char) -> bool {
+    match symbol_counts.get(&target) {
+        Some(&target_count) => symbol_counts
+            .iter()
+            .all(|(&symbol, &count)| symbol == target || count < target_count),
+        None => false,
+    }
+}
+
+// First step (0 being the template itself) after which `target` is the single
+// most common symbol, looking no further than `cap` steps
+pub fn step_symbol_becomes_dominant(
+    template: &PolymerTemplate,
+    rules: &InsertionRules,
+    target: char,
+    cap: u32,
+) -> Option<u32> {
+    let mut pair_counts = create_initial_pair_counts(template);
+    for step in 0..=cap {
+        if step > 0 {
+            pair_counts = pair_counting_step(rules, &pair_counts);
+        }
+        let symbol_counts = compute_symbol_counts(template, &pair_counts);
+        if is_single_most_common(&symbol_counts, target) {
+            return Some(step);
+        }
+    }
+    None
+}
+
+fn most_common_minus_least_common_after_n_steps(
+    template: &PolymerTemplate,
+    rules: &InsertionRules,
+    steps: u32,
+) -> u64 {
+    let symbol_counts = symbol_counts_after_n_steps(template, rules, steps);
+    most_common_minus_least_common(&symbol_counts)
+}
+
+fn most_common_minus_least_common(symbol_counts: &SymbolCounts) -> u64 {
+    if symbol_counts.is_empty() {
+        return 0;
+    }
+    let (min, max) = min_and_max_symbol_occurrences(symbol_counts);
+    max - min
+}
+
+const PART_1_STEPS: u32 = 10;
+const PART_2_STEPS: u32 = 40;
+
+// Both answers from a single run, since the first 10 steps are shared
+pub fn solve(template: &PolymerTemplate, rules: &InsertionRules) -> (u64, u64) {
+    let mut pair_counts = create_initial_pair_counts(template);
+    let mut part_1_answer = 0;
+    for step in 1..=PART_2_STEPS {
+        pair_counts = pair_counting_step(rules, &pair_counts);
+        if step == PART_1_STEPS {
+            let symbol_counts = compute_symbol_counts(template, &pair_counts);
+            part_1_answer = most_common_minus_least_common(&symbol_counts);
+        }
+    }
     let symbol_counts = compute_symbol_counts(template, &pair_counts);
-    let (min, max) = min_and_max_symbol_occurrences(&symbol_counts);
-    max/2 - min/2 // discount double counts
+    let part_2_answer = most_common_minus_least_common(&symbol_counts);
+    (part_1_answer, part_2_answer)
 }
 
-mod part_1 {
+pub mod part_1 {
     use super::*;
 
     pub fn most_common_minus_least_common_after_10_steps(
         template: &PolymerTemplate,
         rules: &InsertionRules,
-    ) -> usize {
-        most_common_minus_least_common_after_n_steps(template, rules, 10)
+    ) -> u64 {
+        most_common_minus_least_common_after_n_steps(template, rules, PART_1_STEPS)
     }
 }
 
-mod part_2 {
+pub mod part_2 {
     use super::*;
 
     pub fn most_common_minus_least_common_after_40_steps(
         template: &PolymerTemplate,
         rules: &InsertionRules,
-    ) -> usize {
-        most_common_minus_least_common_after_n_steps(template, rules, 40)
+    ) -> u64 {
+        most_common_minus_least_common_after_n_steps(template, rules, PART_2_STEPS)
     }
 }
 
 fn main() {
-    let (template, rules) = parse_input(&read_to_string("data/day-14.txt").unwrap());
+    let (template, rules) = match parse_input(&read_to_string("data/day-14.txt").unwrap()) {
+        Ok(input) => input,
+        Err(e) => panic!("Error parsing input file for day 14: {}", e),
+    };
+
+    let (diff_10, diff_40) = solve(&template, &rules);
 
     println!("== PART 1");
-    let diff_10 = part_1::most_common_minus_least_common_after_10_steps(&template, &rules);
     println!("Difference between most and least common element after 10 steps: {diff_10}");
 
     println!();
 
     println!("== PART 2");
-    let diff_40 = part_2::most_common_minus_least_common_after_40_steps(&template, &rules);
     println!("Difference between most and least common element after 40 steps: {diff_40}");
 }
 
@@ -162,11 +326,34 @@ CN -> C";
     #[test]
     fn test_input_parsing() {
         let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
-        let (template, rules) = parse_input(&test_input);
+        let (template, rules) = parse_input(&test_input).unwrap();
         assert_eq!(template, vec!['N', 'N', 'C', 'B']);
         assert_eq!(rules.len(), 16);
         let cb_rule = rules.get(&('C', 'B'));
-        assert_eq!(cb_rule, Some(&'H'));
+        assert_eq!(cb_rule.map(String::as_str), Some("H"));
+    }
+
+    #[test]
+    fn test_rule_parsing_without_spaces() {
+        let test_input = "NNCB\n\nCH->B";
+        assert_eq!(
+            parse_input(test_input),
+            Err(InputParsingError::MalformedRule(String::from("CH->B")))
+        );
+    }
+
+    #[test]
+    fn test_rule_parsing_with_wrong_length_pair() {
+        let test_input = "NNCB\n\nCH -> B\nC -> B";
+        assert_eq!(
+            parse_input(test_input),
+            Err(InputParsingError::MalformedRule(String::from("C -> B")))
+        );
+        let test_input = "NNCB\n\nCH -> ";
+        assert_eq!(
+            parse_input(test_input),
+            Err(InputParsingError::MalformedRule(String::from("CH -> ")))
+        );
     }
 
     #[test]
@@ -191,8 +378,194 @@ CN -> C";
     #[test]
     fn test_10_steps() {
         let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
-        let (template, rules) = parse_input(&test_input);
+        let (template, rules) = parse_input(&test_input).unwrap();
         let diff = most_common_minus_least_common_after_n_steps(&template, &rules, 10);
         assert_eq!(1588, diff);
     }
+
+    #[test]
+    fn test_symbol_counts_after_10_steps() {
+        let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        let symbol_counts = symbol_counts_after_n_steps(&template, &rules, 10);
+        assert_eq!(Some(&1749), symbol_counts.get(&'B'));
+        assert_eq!(Some(&161), symbol_counts.get(&'H'));
+        assert_eq!(3073, symbol_counts.values().sum::<u64>());
+    }
+
+    #[test]
+    fn test_pair_without_insertion_rule_survives() {
+        let test_input = "NNCB
+
+NN -> C
+NC -> B";
+        let (template, rules) = parse_input(test_input).unwrap();
+        let pair_counts = pair_counting_step(&rules, &create_initial_pair_counts(&template));
+        // NNCB -> NCNBCB, with CB left as is
+        assert_eq!(Some(&1), pair_counts.get(&('C', 'B')));
+        assert_eq!(Some(&1), pair_counts.get(&('N', 'C')));
+        assert_eq!(Some(&1), pair_counts.get(&('C', 'N')));
+        assert_eq!(Some(&1), pair_counts.get(&('N', 'B')));
+        assert_eq!(Some(&1), pair_counts.get(&('B', 'C')));
+        assert_eq!(5, pair_counts.values().sum::<u64>());
+    }
+
+    #[test]
+    fn test_compute_symbol_counts_counts_each_symbol_once() {
+        // NNCB -> NCNBCHB after one step
+        let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        let pair_counts = pair_counting_step(&rules, &create_initial_pair_counts(&template));
+        let symbol_counts = compute_symbol_counts(&template, &pair_counts);
+        assert_eq!(
+            symbol_counts,
+            SymbolCounts::from([('N', 2), ('C', 2), ('B', 2), ('H', 1)])
+        );
+        let diff = most_common_minus_least_common_after_n_steps(&template, &rules, 1);
+        assert_eq!(1, diff);
+    }
+
+    #[test]
+    fn test_symbol_counts_with_odd_counts() {
+        // NBBBCNCCNBBNBNBBCHBHHBCHB after three steps
+        let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        let symbol_counts = symbol_counts_after_n_steps(&template, &rules, 3);
+        assert_eq!(
+            symbol_counts,
+            SymbolCounts::from([('N', 5), ('B', 11), ('C', 5), ('H', 4)])
+        );
+        let diff = most_common_minus_least_common_after_n_steps(&template, &rules, 3);
+        assert_eq!(7, diff);
+
+        // NCN -> NBCCN. The Ns only sit at the ends, so each is in a single
+        // pair: halving the symbols counted across pairs (NB, BC, CC, CN)
+        // would give N 2 / 2 = 1 instead of 2
+        let test_input = format!("NCN\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        let symbol_counts = symbol_counts_after_n_steps(&template, &rules, 1);
+        assert_eq!(
+            symbol_counts,
+            SymbolCounts::from([('N', 2), ('B', 1), ('C', 2)])
+        );
+    }
+
+    #[test]
+    fn test_multi_character_insertion() {
+        let test_input = "NCB
+
+NC -> XY
+CB -> Z";
+        let (template, rules) = parse_input(test_input).unwrap();
+        // NCB -> NXYCZB
+        let pair_counts = pair_counting_step(&rules, &create_initial_pair_counts(&template));
+        assert_eq!(
+            pair_counts,
+            PairCounts::from([
+                (('N', 'X'), 1),
+                (('X', 'Y'), 1),
+                (('Y', 'C'), 1),
+                (('C', 'Z'), 1),
+                (('Z', 'B'), 1),
+            ])
+        );
+        let symbol_counts = symbol_counts_after_n_steps(&template, &rules, 1);
+        assert_eq!(6, symbol_counts.values().sum::<u64>());
+        assert_eq!(Some(&1), symbol_counts.get(&'X'));
+    }
+
+    #[test]
+    fn test_pair_counts_per_step() {
+        let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        let pair_counts_per_step = pair_counts_per_step(&template, &rules, 3);
+        assert_eq!(4, pair_counts_per_step.len());
+        // A polymer of length n has n - 1 pairs; since each step takes its
+        // length to 2n - 1, the number of pairs doubles
+        let pair_totals: Vec<u64> = pair_counts_per_step
+            .iter()
+            .map(|pair_counts| pair_counts.values().sum())
+            .collect();
+        assert_eq!(vec![3, 6, 12, 24], pair_totals);
+        assert_eq!(Some(&1), pair_counts_per_step[1].get(&('C', 'H')));
+    }
+
+    #[test]
+    fn test_grow_polymer() {
+        let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        assert_eq!("NCNBCHB", grow_polymer(&template, &rules, 1));
+        assert_eq!(
+            "NBBNBNBBCCNBCNCCNBBNBBNBBBNBBNBBCBHCBHHNHCBBCBHCB",
+            grow_polymer(&template, &rules, 4)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_grow_polymer_for_too_many_steps() {
+        let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        grow_polymer(&template, &rules, 40);
+    }
+
+    #[test]
+    fn test_40_steps() {
+        let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        let diff = most_common_minus_least_common_after_n_steps(&template, &rules, 40);
+        assert_eq!(2188189693529, diff);
+    }
+
+    #[test]
+    fn test_one_char_template() {
+        let test_input = format!("N\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        assert!(create_initial_pair_counts(&template).is_empty());
+        let symbol_counts = symbol_counts_after_n_steps(&template, &rules, 10);
+        assert_eq!(SymbolCounts::from([('N', 1)]), symbol_counts);
+        let diff = most_common_minus_least_common_after_n_steps(&template, &rules, 10);
+        assert_eq!(0, diff);
+    }
+
+    #[test]
+    fn test_empty_template() {
+        let test_input = format!("\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        assert!(template.is_empty());
+        assert!(symbol_counts_after_n_steps(&template, &rules, 10).is_empty());
+        let diff = most_common_minus_least_common_after_n_steps(&template, &rules, 10);
+        assert_eq!(0, diff);
+        assert_eq!("", grow_polymer(&template, &rules, 3));
+    }
+
+    #[test]
+    fn test_step_symbol_becomes_dominant() {
+        let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        // NNCB, NCNBCHB, NBCCNBBBCBHCB
+        let dominant_step =
+            |target, cap| step_symbol_becomes_dominant(&template, &rules, target, cap);
+        assert_eq!(Some(0), dominant_step('N', 10));
+        assert_eq!(Some(2), dominant_step('B', 10));
+        assert_eq!(None, dominant_step('B', 1));
+        assert_eq!(None, dominant_step('H', 10));
+    }
+
+    #[test]
+    fn test_count_of() {
+        let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        let symbol_counts = symbol_counts_after_n_steps(&template, &rules, 10);
+        assert_eq!(symbol_counts[&'N'], count_of(&template, &rules, 10, 'N'));
+        assert_eq!(865, count_of(&template, &rules, 10, 'N'));
+        assert_eq!(0, count_of(&template, &rules, 10, 'X'));
+    }
+
+    #[test]
+    fn test_solve() {
+        let test_input = format!("{TEST_TEMPLATE_STR}\n\n{TEST_RULES}");
+        let (template, rules) = parse_input(&test_input).unwrap();
+        assert_eq!((1588, 2188189693529), solve(&template, &rules));
+    }
 }
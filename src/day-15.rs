@@ -1,8 +1,10 @@
-use std::collections::{BinaryHeap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt::Display;
 use std::fs::read_to_string;
 
 #[derive(Debug, Hash, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct Position {
+pub struct Position {
     row: usize,
     col: usize,
 }
@@ -40,6 +42,7 @@ impl PartialOrd for NodeInfo {
     }
 }
 
+#[derive(Clone)]
 pub struct RiskLevelMap {
     nodes: Vec<Vec<NodeInfo>>,
     width: usize,
@@ -47,6 +50,34 @@ pub struct RiskLevelMap {
 }
 
 impl RiskLevelMap {
+    // Builds a map from a non-empty, rectangular grid of risk levels
+    pub fn from_grid(costs: Vec<Vec<u8>>) -> Self {
+        assert!(
+            costs.first().is_some_and(|first_row| !first_row.is_empty()),
+            "Risk level grid must not be empty"
+        );
+        assert!(
+            costs.iter().all(|row| row.len() == costs[0].len()),
+            "Risk level grid must be rectangular"
+        );
+        let nodes = costs
+            .into_iter()
+            .enumerate()
+            .map(|(row, row_costs)| {
+                row_costs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(col, edge_cost)| NodeInfo {
+                        position: Position { row, col },
+                        edge_cost,
+                        dist_from_src: usize::MAX,
+                    })
+                    .collect()
+            })
+            .collect();
+        Self::new(nodes)
+    }
+
     fn new(nodes: Vec<Vec<NodeInfo>>) -> Self {
         let width = &nodes[0].len();
         let height = &nodes.len();
@@ -58,132 +89,344 @@ impl RiskLevelMap {
     }
 }
 
-fn parse_input(input: &str) -> RiskLevelMap {
-    let rows_str: Vec<&str> = input.split_terminator("\n").collect();
-    let mut nodes = Vec::<Vec<NodeInfo>>::new();
-    let mut row_idx = 0;
-    for row_str in rows_str {
-        let row: Vec<NodeInfo> = row_str
-            .chars()
-            .enumerate()
-            .map(|(col_idx, c)| NodeInfo {
-                position: Position {
-                    row: row_idx,
-                    col: col_idx,
-                },
-                edge_cost: c.to_digit(10).unwrap() as u8,
-                dist_from_src: usize::MAX,
-            })
-            .collect();
-        nodes.push(row);
-        row_idx += 1;
-    }
-    RiskLevelMap::new(nodes)
+#[derive(Debug, PartialEq)]
+enum InputParsingError {
+    EmptyMap,
+    InvalidRiskLevel {
+        row: usize,
+        col: usize,
+        found: char,
+    },
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
 }
 
-fn candidate_neighbors(
-    map: &RiskLevelMap,
-    position: &Position,
-    finished_positions: &HashSet<Position>,
-) -> Vec<Position> {
-    let mut candidates = Vec::new();
-
-    // left
-    if position.col > 0 {
-        let candidate = Position {
-            row: position.row,
-            col: position.col - 1,
-        };
-        if !finished_positions.contains(&candidate) {
-            candidates.push(candidate);
+impl Display for InputParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyMap => write!(f, "Risk level map has no positions"),
+            Self::InvalidRiskLevel { row, col, found } => {
+                write!(f, "Invalid risk level {found:?} at row {row}, column {col}")
+            }
+            Self::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(f, "Row {row} has {found} positions; expected {expected}"),
         }
     }
-    // top
-    if position.row > 0 {
-        let candidate = Position {
-            row: position.row - 1,
-            col: position.col,
-        };
-        if !finished_positions.contains(&candidate) {
-            candidates.push(candidate);
+}
+
+fn parse_input(input: &str) -> Result<RiskLevelMap, InputParsingError> {
+    let rows_str: Vec<&str> = input.split_terminator("\n").collect();
+    let mut costs = Vec::<Vec<u8>>::new();
+    for (row_idx, row_str) in rows_str.into_iter().enumerate() {
+        let mut row: Vec<u8> = Vec::new();
+        for (col_idx, c) in row_str.chars().enumerate() {
+            match c.to_digit(10) {
+                Some(edge_cost) => row.push(edge_cost as u8),
+                None => {
+                    return Err(InputParsingError::InvalidRiskLevel {
+                        row: row_idx,
+                        col: col_idx,
+                        found: c,
+                    })
+                }
+            }
         }
-    }
-    // right
-    if position.col < map.width - 1 {
-        let candidate = Position {
-            row: position.row,
-            col: position.col + 1,
-        };
-        if !finished_positions.contains(&candidate) {
-            candidates.push(candidate);
+        if let Some(first_row) = costs.first() {
+            if row.len() != first_row.len() {
+                return Err(InputParsingError::RaggedRow {
+                    row: row_idx,
+                    expected: first_row.len(),
+                    found: row.len(),
+                });
+            }
         }
+        costs.push(row);
     }
-    // bottom
-    if position.row < map.height - 1 {
-        let candidate = Position {
-            row: position.row + 1,
-            col: position.col,
-        };
-        if !finished_positions.contains(&candidate) {
-            candidates.push(candidate);
-        }
+    if costs.first().is_none_or(|first_row| first_row.is_empty()) {
+        return Err(InputParsingError::EmptyMap);
     }
+    Ok(RiskLevelMap::from_grid(costs))
+}
 
-    candidates
+// Whether moves are limited to the four orthogonal directions or may also be
+// diagonal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    Four,
+    Eight,
 }
 
-fn shortest_path_to_bottom_right(map: &mut RiskLevelMap) {
-    let end_pos = Position {
-        row: map.height - 1,
-        col: map.width - 1,
+// Up to eight neighbors, gathered on the stack since this runs for every node
+// popped from the heap
+fn candidate_neighbors<'a>(
+    map: &RiskLevelMap,
+    position: &Position,
+    connectivity: Connectivity,
+    finished_positions: &'a HashSet<Position>,
+) -> impl Iterator<Item = Position> + 'a {
+    let Position { row, col } = *position;
+    let left = col.checked_sub(1);
+    let top = row.checked_sub(1);
+    let right = (col < map.width - 1).then_some(col + 1);
+    let bottom = (row < map.height - 1).then_some(row + 1);
+
+    let orthogonal = [
+        (Some(row), left),
+        (top, Some(col)),
+        (Some(row), right),
+        (bottom, Some(col)),
+    ];
+    let diagonal = [(top, left), (top, right), (bottom, right), (bottom, left)];
+    let diagonal_count = match connectivity {
+        Connectivity::Four => 0,
+        Connectivity::Eight => diagonal.len(),
     };
 
-    map.nodes[0][0].dist_from_src = 0;
+    orthogonal
+        .into_iter()
+        .chain(diagonal.into_iter().take(diagonal_count))
+        .filter_map(|(row, col)| {
+            Some(Position {
+                row: row?,
+                col: col?,
+            })
+        })
+        .filter(move |candidate| !finished_positions.contains(candidate))
+}
+
+// Only nodes popped from the heap are relaxed from, and those always carry a
+// real distance; an unvisited node's `usize::MAX` would overflow here
+fn relaxed_distance(base_dist: usize, edge_cost: u8) -> usize {
+    debug_assert_ne!(base_dist, usize::MAX, "Relaxing from an unvisited node");
+    base_dist + edge_cost as usize
+}
+
+// Fills in `dist_from_src` and returns the node each position was last
+// reached from, so the route can be walked back from the goal, along with the
+// number of nodes expanded. Without a goal the whole map is explored
+fn dijkstra(
+    map: &mut RiskLevelMap,
+    start: Position,
+    goal: Option<Position>,
+    connectivity: Connectivity,
+) -> (HashMap<Position, Position>, usize) {
+    map.nodes[start.row][start.col].dist_from_src = 0;
 
     let mut heap: BinaryHeap<NodeInfo> = BinaryHeap::new();
-    heap.push(map.nodes[0][0].clone());
+    heap.push(map.nodes[start.row][start.col].clone());
 
+    let mut predecessors = HashMap::new();
     let mut finished_positions = HashSet::new();
     while let Some(curr_node_info) = heap.pop() {
-        let neighbor_positions =
-            candidate_neighbors(&map, &curr_node_info.position, &finished_positions);
+        // A node's distance is only final once it's popped; entries pushed
+        // before a later improvement are stale and can be skipped
+        if !finished_positions.insert(curr_node_info.position) {
+            continue;
+        }
+        if Some(curr_node_info.position) == goal {
+            break;
+        }
+        let neighbor_positions = candidate_neighbors(
+            map,
+            &curr_node_info.position,
+            connectivity,
+            &finished_positions,
+        );
         for neighbor_position in neighbor_positions {
             let neighbor_info = map.nodes[neighbor_position.row][neighbor_position.col];
-            let cmp_dist = curr_node_info.dist_from_src + neighbor_info.edge_cost as usize;
+            let cmp_dist = relaxed_distance(curr_node_info.dist_from_src, neighbor_info.edge_cost);
             if neighbor_info.dist_from_src > cmp_dist {
                 map.nodes[neighbor_position.row][neighbor_position.col].dist_from_src = cmp_dist;
                 heap.push(map.nodes[neighbor_position.row][neighbor_position.col].clone());
-                if neighbor_info.position == end_pos {
-                    return;
-                }
+                predecessors.insert(neighbor_position, curr_node_info.position);
+            }
+        }
+    }
+    (predecessors, finished_positions.len())
+}
+
+fn shortest_path_to_bottom_right(map: &mut RiskLevelMap) -> HashMap<Position, Position> {
+    let end_pos = Position {
+        row: map.height - 1,
+        col: map.width - 1,
+    };
+    let (predecessors, _) = dijkstra(
+        map,
+        Position { row: 0, col: 0 },
+        Some(end_pos),
+        Connectivity::Four,
+    );
+    predecessors
+}
+
+// Cost of the cheapest route from `start` to `goal`, or `None` if either lies
+// outside the map or the goal can't be reached
+pub fn shortest_path_between(map: &RiskLevelMap, start: Position, goal: Position) -> Option<usize> {
+    let is_inside = |position: &Position| position.row < map.height && position.col < map.width;
+    if !is_inside(&start) || !is_inside(&goal) {
+        return None;
+    }
+    let mut map = map.clone();
+    dijkstra(&mut map, start, Some(goal), Connectivity::Four);
+    match map.nodes[goal.row][goal.col].dist_from_src {
+        usize::MAX => None,
+        dist => Some(dist),
+    }
+}
+
+pub fn shortest_path_cost(map: &RiskLevelMap) -> usize {
+    shortest_path_cost_with_connectivity(map, Connectivity::Four)
+}
+
+pub fn shortest_path_cost_with_connectivity(
+    map: &RiskLevelMap,
+    connectivity: Connectivity,
+) -> usize {
+    shortest_path_stats(map, connectivity).cost
+}
+
+// Cost to the bottom right and how many nodes the search had to expand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathStats {
+    pub cost: usize,
+    pub expanded_nodes: usize,
+}
+
+// Runs the search on a copy of the map, leaving the original untouched
+pub fn shortest_path_stats(map: &RiskLevelMap, connectivity: Connectivity) -> PathStats {
+    let mut map = map.clone();
+    let end_pos = Position {
+        row: map.height - 1,
+        col: map.width - 1,
+    };
+    let (_, expanded_nodes) = dijkstra(
+        &mut map,
+        Position { row: 0, col: 0 },
+        Some(end_pos),
+        connectivity,
+    );
+    PathStats {
+        cost: map.nodes[end_pos.row][end_pos.col].dist_from_src,
+        expanded_nodes,
+    }
+}
+
+// Finalized distance from the top left to every position
+pub fn distance_field(map: &RiskLevelMap) -> Vec<Vec<usize>> {
+    let mut map = map.clone();
+    dijkstra(
+        &mut map,
+        Position { row: 0, col: 0 },
+        None,
+        Connectivity::Four,
+    );
+    map.nodes
+        .iter()
+        .map(|row| row.iter().map(|node| node.dist_from_src).collect())
+        .collect()
+}
+
+// Total risk and the positions from the top left to the bottom right
+pub fn shortest_path(map: &RiskLevelMap) -> (usize, Vec<Position>) {
+    let mut map = map.clone();
+    let predecessors = shortest_path_to_bottom_right(&mut map);
+    let mut position = Position {
+        row: map.height - 1,
+        col: map.width - 1,
+    };
+    let mut path = vec![position];
+    while let Some(&predecessor) = predecessors.get(&position) {
+        path.push(predecessor);
+        position = predecessor;
+    }
+    path.reverse();
+    (map.nodes[map.height - 1][map.width - 1].dist_from_src, path)
+}
+
+// Same search guided by the Manhattan distance to the bottom right, which never
+// overestimates the remaining cost as long as every risk level is at least 1
+pub fn shortest_path_astar(map: &RiskLevelMap) -> usize {
+    shortest_path_astar_stats(map).cost
+}
+
+pub fn shortest_path_astar_stats(map: &RiskLevelMap) -> PathStats {
+    let start_pos = Position { row: 0, col: 0 };
+    let end_pos = Position {
+        row: map.height - 1,
+        col: map.width - 1,
+    };
+    let heuristic =
+        |position: &Position| (end_pos.row - position.row) + (end_pos.col - position.col);
+
+    let mut dists = vec![vec![usize::MAX; map.width]; map.height];
+    dists[0][0] = 0;
+
+    // Entries are (estimated total cost, distance from source, position)
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((heuristic(&start_pos), 0, start_pos)));
+
+    let mut finished_positions = HashSet::new();
+    while let Some(Reverse((_, dist, position))) = heap.pop() {
+        if !finished_positions.insert(position) {
+            continue;
+        }
+        if position == end_pos {
+            break;
+        }
+        for neighbor_position in
+            candidate_neighbors(map, &position, Connectivity::Four, &finished_positions)
+        {
+            let edge_cost = map.nodes[neighbor_position.row][neighbor_position.col].edge_cost;
+            let cmp_dist = relaxed_distance(dist, edge_cost);
+            if dists[neighbor_position.row][neighbor_position.col] > cmp_dist {
+                dists[neighbor_position.row][neighbor_position.col] = cmp_dist;
+                let estimate = cmp_dist + heuristic(&neighbor_position);
+                heap.push(Reverse((estimate, cmp_dist, neighbor_position)));
             }
         }
-        finished_positions.insert(curr_node_info.position);
+    }
+    PathStats {
+        cost: dists[end_pos.row][end_pos.col],
+        expanded_nodes: finished_positions.len(),
     }
 }
 
 mod part_2 {
     use super::*;
 
-    const EXPANSION_FACTOR: usize = 5;
+    pub const EXPANSION_FACTOR: usize = 5;
+
+    // Risk levels above 9 wrap back around to 1, however many times they
+    // overflow. Anything up to 9, including 0, is left as it is
+    pub fn wrap_risk_level(value: usize) -> u8 {
+        if value <= 9 {
+            return value as u8;
+        }
+        ((value - 1) % 9 + 1) as u8
+    }
 
-    pub fn expand_map(map: &RiskLevelMap) -> RiskLevelMap {
-        let new_height = EXPANSION_FACTOR * map.height;
-        let new_width = EXPANSION_FACTOR * map.width;
+    // Tiles the map `factor` times in each direction, each tile one risk level
+    // higher than the one to its left or above
+    pub fn expand_map(map: &RiskLevelMap, factor: usize) -> RiskLevelMap {
+        let new_height = factor * map.height;
+        let new_width = factor * map.width;
         let mut new_nodes = vec![vec![NodeInfo::default(); new_width]; new_height];
         for row in 0..new_height {
             let src_row = row % map.height;
-            let vertical_cost_increase = (row / map.height) as u8;
+            let vertical_cost_increase = row / map.height;
             for col in 0..new_width {
                 let src_col = col % map.width;
-                let horizontal_cost_increase = (col / map.width) as u8;
-                let mut new_cost = map.nodes[src_row][src_col].edge_cost
+                let horizontal_cost_increase = col / map.width;
+                let new_cost = map.nodes[src_row][src_col].edge_cost as usize
                     + vertical_cost_increase
                     + horizontal_cost_increase;
-                if new_cost > 9 {
-                    new_cost -= 9;
-                }
-                new_nodes[row][col].edge_cost = new_cost;
+                new_nodes[row][col].edge_cost = wrap_risk_level(new_cost);
                 new_nodes[row][col].position = Position { row, col };
             }
         }
@@ -192,23 +435,24 @@ mod part_2 {
 }
 
 fn main() {
+    let map = match parse_input(&read_to_string("data/day-15.txt").unwrap()) {
+        Ok(map) => map,
+        Err(e) => panic!("Error parsing input file for day 15: {}", e),
+    };
+
     println!("== PART 1");
-    let mut map = parse_input(&read_to_string("data/day-15.txt").unwrap());
-    shortest_path_to_bottom_right(&mut map);
     println!(
         "Minimum cost to get to the bottom right position: {}",
-        map.nodes[map.height - 1][map.width - 1].dist_from_src
+        shortest_path_cost(&map)
     );
 
     println!();
 
     println!("== PART 2");
-    let map = parse_input(&read_to_string("data/day-15.txt").unwrap());
-    let mut expanded_map = part_2::expand_map(&map);
-    shortest_path_to_bottom_right(&mut expanded_map);
+    let expanded_map = part_2::expand_map(&map, part_2::EXPANSION_FACTOR);
     println!(
         "Minimum cost to get to the bottom right position: {}",
-        expanded_map.nodes[expanded_map.height - 1][expanded_map.width - 1].dist_from_src
+        shortest_path_cost(&expanded_map)
     );
 }
 
@@ -216,8 +460,60 @@ fn main() {
 mod tests {
     use super::*;
 
+    // The original implementation, kept to check the iterator version against
+    fn allocating_candidate_neighbors(
+        map: &RiskLevelMap,
+        position: &Position,
+        finished_positions: &HashSet<Position>,
+    ) -> Vec<Position> {
+        let mut candidates = Vec::new();
+
+        // left
+        if position.col > 0 {
+            let candidate = Position {
+                row: position.row,
+                col: position.col - 1,
+            };
+            if !finished_positions.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+        // top
+        if position.row > 0 {
+            let candidate = Position {
+                row: position.row - 1,
+                col: position.col,
+            };
+            if !finished_positions.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+        // right
+        if position.col < map.width - 1 {
+            let candidate = Position {
+                row: position.row,
+                col: position.col + 1,
+            };
+            if !finished_positions.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+        // bottom
+        if position.row < map.height - 1 {
+            let candidate = Position {
+                row: position.row + 1,
+                col: position.col,
+            };
+            if !finished_positions.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+
+        candidates
+    }
+
     fn example_data() -> RiskLevelMap {
-        parse_input(&read_to_string("data/day-15-example.txt").unwrap())
+        parse_input(&read_to_string("data/day-15-example.txt").unwrap()).unwrap()
         // parse_input(&read_to_string("data/day-15-min-example.txt").unwrap())
     }
 
@@ -267,7 +563,8 @@ mod tests {
         let map = example_data();
         let position = Position { row: 0, col: 0 };
         let finished_positions = HashSet::new();
-        let neighbors = candidate_neighbors(&map, &position, &finished_positions);
+        let neighbors: Vec<Position> =
+            candidate_neighbors(&map, &position, Connectivity::Four, &finished_positions).collect();
         assert_eq!(2, neighbors.len());
         assert!(neighbors.contains(&Position { row: 0, col: 1 }));
         assert!(neighbors.contains(&Position { row: 1, col: 0 }));
@@ -278,7 +575,8 @@ mod tests {
         let map = example_data();
         let position = Position { row: 0, col: 1 };
         let finished_positions = HashSet::new();
-        let neighbors = candidate_neighbors(&map, &position, &finished_positions);
+        let neighbors: Vec<Position> =
+            candidate_neighbors(&map, &position, Connectivity::Four, &finished_positions).collect();
         assert_eq!(3, neighbors.len());
         assert!(neighbors.contains(&Position { row: 0, col: 0 }));
         assert!(neighbors.contains(&Position { row: 0, col: 2 }));
@@ -329,4 +627,245 @@ mod tests {
             map.nodes[map.width - 1][map.height - 1]
         );
     }
+
+    #[test]
+    fn test_shortest_path_on_winding_grid() {
+        // The cheapest route snakes around the 9s instead of cutting through.
+        // This only checks the route: every edge into a node costs that node's
+        // risk, so stopping at the first relaxation of the end node, as the
+        // search used to, gives the same cost and no grid tells them apart
+        let mut map = parse_input("119111\n919191\n911191\n").unwrap();
+        shortest_path_to_bottom_right(&mut map);
+        assert_eq!(11, map.nodes[map.height - 1][map.width - 1].dist_from_src);
+    }
+
+    #[test]
+    fn test_shortest_path_cost() {
+        let map = example_data();
+        assert_eq!(40, shortest_path_cost(&map));
+        assert_eq!(
+            usize::MAX,
+            map.nodes[map.height - 1][map.width - 1].dist_from_src
+        );
+        assert_eq!(
+            315,
+            shortest_path_cost(&part_2::expand_map(&map, part_2::EXPANSION_FACTOR))
+        );
+    }
+
+    #[test]
+    fn test_shortest_path_positions() {
+        let map = example_data();
+        let (cost, path) = shortest_path(&map);
+        assert_eq!(40, cost);
+        assert_eq!(Some(&Position { row: 0, col: 0 }), path.first());
+        assert_eq!(Some(&Position { row: 9, col: 9 }), path.last());
+        let path_risk: usize = path[1..]
+            .iter()
+            .map(|position| map.nodes[position.row][position.col].edge_cost as usize)
+            .sum();
+        assert_eq!(cost, path_risk);
+    }
+
+    #[test]
+    fn test_astar_matches_dijkstra() {
+        let map = example_data();
+        assert_eq!(shortest_path_cost(&map), shortest_path_astar(&map));
+        let expanded_map = part_2::expand_map(&map, part_2::EXPANSION_FACTOR);
+        assert_eq!(315, shortest_path_astar(&expanded_map));
+        assert_eq!(
+            shortest_path_cost(&expanded_map),
+            shortest_path_astar(&expanded_map)
+        );
+    }
+
+    #[test]
+    fn test_shortest_path_between() {
+        let map = example_data();
+        let start = Position { row: 0, col: 0 };
+        let end = Position { row: 9, col: 9 };
+        assert_eq!(Some(40), shortest_path_between(&map, start, end));
+        // Straight along row 1 (1381373672) beats detouring through rows 0 or 2
+        let from = Position { row: 1, col: 1 };
+        let to = Position { row: 1, col: 4 };
+        assert_eq!(Some(8 + 1 + 3), shortest_path_between(&map, from, to));
+        assert_eq!(Some(0), shortest_path_between(&map, from, from));
+        let outside = Position { row: 10, col: 0 };
+        assert_eq!(None, shortest_path_between(&map, start, outside));
+    }
+
+    #[test]
+    fn test_wrap_risk_level() {
+        assert_eq!(1, part_2::wrap_risk_level(1));
+        assert_eq!(9, part_2::wrap_risk_level(9));
+        assert_eq!(1, part_2::wrap_risk_level(10));
+        assert_eq!(8, part_2::wrap_risk_level(17));
+        assert_eq!(9, part_2::wrap_risk_level(18));
+        // 9 + 5 + 5 wraps twice
+        assert_eq!(1, part_2::wrap_risk_level(19));
+        assert_eq!(5, part_2::wrap_risk_level(23));
+        assert_eq!(0, part_2::wrap_risk_level(0));
+    }
+
+    #[test]
+    fn test_expand_map_wraps_costs() {
+        let map = example_data();
+        let expanded_map = part_2::expand_map(&map, part_2::EXPANSION_FACTOR);
+        // The 7 at (0, 4) grows to 7 + 4 + 4 = 15 in the bottom right tile
+        assert_eq!(7, map.nodes[0][4].edge_cost);
+        assert_eq!(6, expanded_map.nodes[40][44].edge_cost);
+    }
+
+    #[test]
+    fn test_expand_map_with_zero_risk_level() {
+        let map = parse_input("09\n").unwrap();
+        let expanded_map = part_2::expand_map(&map, part_2::EXPANSION_FACTOR);
+        // Like the original expansion, a 0 only ever grows up to 8
+        assert_eq!(0, expanded_map.nodes[0][0].edge_cost);
+        assert_eq!(1, expanded_map.nodes[0][2].edge_cost);
+        assert_eq!(8, expanded_map.nodes[4][8].edge_cost);
+        // while the 9 next to it wraps
+        assert_eq!(8, expanded_map.nodes[4][9].edge_cost);
+    }
+
+    #[test]
+    fn test_input_parsing_with_ragged_row() {
+        assert_eq!(
+            Err(InputParsingError::RaggedRow {
+                row: 1,
+                expected: 3,
+                found: 2
+            }),
+            parse_input("123\n45\n678\n").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_input_parsing_with_invalid_risk_level() {
+        assert_eq!(
+            Err(InputParsingError::InvalidRiskLevel {
+                row: 1,
+                col: 2,
+                found: 'x'
+            }),
+            parse_input("123\n45x\n678\n").map(|_| ())
+        );
+        assert_eq!(
+            Err(InputParsingError::EmptyMap),
+            parse_input("").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_candidate_neighbors_match_allocating_version() {
+        let map = example_data();
+        let mut finished_positions = HashSet::new();
+        finished_positions.insert(Position { row: 4, col: 5 });
+        finished_positions.insert(Position { row: 0, col: 1 });
+        for position in [
+            Position { row: 0, col: 0 },
+            Position { row: 9, col: 9 },
+            Position { row: 0, col: 5 },
+            Position { row: 9, col: 3 },
+            Position { row: 4, col: 4 },
+            Position { row: 5, col: 5 },
+        ] {
+            let neighbors: Vec<Position> =
+                candidate_neighbors(&map, &position, Connectivity::Four, &finished_positions)
+                    .collect();
+            assert_eq!(
+                allocating_candidate_neighbors(&map, &position, &finished_positions),
+                neighbors
+            );
+        }
+    }
+
+    #[test]
+    fn test_expand_map_by_factor_2() {
+        let map = example_data();
+        let expanded_map = part_2::expand_map(&map, 2);
+        assert_eq!(20, expanded_map.width);
+        assert_eq!(20, expanded_map.height);
+        assert_eq!(3, expanded_map.nodes[10][10].edge_cost);
+        assert_eq!(9, expanded_map.nodes[3][2].edge_cost);
+        assert_eq!(1, expanded_map.nodes[13][2].edge_cost);
+        assert_eq!(1, expanded_map.nodes[3][12].edge_cost);
+        assert_eq!(2, expanded_map.nodes[13][12].edge_cost);
+        let bottom_right = Position { row: 19, col: 19 };
+        assert_eq!(bottom_right, expanded_map.nodes[19][19].position);
+    }
+
+    #[test]
+    fn test_distance_field() {
+        let map = example_data();
+        let field = distance_field(&map);
+        assert_eq!(0, field[0][0]);
+        assert_eq!(1, field[0][1]);
+        assert_eq!(1, field[1][0]);
+        assert_eq!(40, field[9][9]);
+        assert!(field.iter().flatten().all(|&dist| dist != usize::MAX));
+    }
+
+    #[test]
+    fn test_candidate_neighbors_with_diagonals() {
+        let map = example_data();
+        let finished_positions = HashSet::new();
+        let corner = Position { row: 0, col: 0 };
+        let interior = Position { row: 4, col: 4 };
+        let neighbor_count = |position| {
+            candidate_neighbors(&map, &position, Connectivity::Eight, &finished_positions).count()
+        };
+        assert_eq!(3, neighbor_count(corner));
+        assert_eq!(8, neighbor_count(interior));
+    }
+
+    #[test]
+    fn test_eight_connected_cost() {
+        let map = example_data();
+        let four_connected_cost = shortest_path_cost_with_connectivity(&map, Connectivity::Four);
+        let eight_connected_cost = shortest_path_cost_with_connectivity(&map, Connectivity::Eight);
+        assert_eq!(40, four_connected_cost);
+        assert!(eight_connected_cost <= four_connected_cost);
+    }
+
+    #[test]
+    fn test_relaxed_distance() {
+        assert_eq!(0, relaxed_distance(0, 0));
+        assert_eq!(49, relaxed_distance(40, 9));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Relaxing from an unvisited node")]
+    fn test_relaxed_distance_from_unvisited_node() {
+        let unvisited = NodeInfo::default();
+        relaxed_distance(unvisited.dist_from_src, 1);
+    }
+
+    #[test]
+    fn test_from_grid() {
+        let map = RiskLevelMap::from_grid(vec![vec![1, 9, 1], vec![1, 9, 1], vec![1, 1, 1]]);
+        assert_eq!(3, map.width);
+        assert_eq!(3, map.height);
+        assert_eq!(Position { row: 1, col: 2 }, map.nodes[1][2].position);
+        assert_eq!(4, shortest_path_cost(&map));
+    }
+
+    #[test]
+    #[should_panic(expected = "Risk level grid must be rectangular")]
+    fn test_from_ragged_grid() {
+        RiskLevelMap::from_grid(vec![vec![1, 2, 3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_astar_expands_no_more_nodes_than_dijkstra() {
+        let map = example_data();
+        let expanded_map = part_2::expand_map(&map, part_2::EXPANSION_FACTOR);
+        let dijkstra_stats = shortest_path_stats(&expanded_map, Connectivity::Four);
+        let astar_stats = shortest_path_astar_stats(&expanded_map);
+        assert_eq!(315, dijkstra_stats.cost);
+        assert_eq!(dijkstra_stats.cost, astar_stats.cost);
+        assert!(astar_stats.expanded_nodes <= dijkstra_stats.expanded_nodes);
+        assert!(dijkstra_stats.expanded_nodes <= expanded_map.width * expanded_map.height);
+    }
 }
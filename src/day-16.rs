@@ -1,5 +1,7 @@
+use std::collections::HashMap;
+use std::fmt::Display;
 use std::fs::read_to_string;
-use aoc_2021::{hex_str_to_u8_vec, extract_bits};
+use aoc_2021::{hex_str_to_u8_vec, extract_bits, try_hex_str_to_u8_vec, ExtractError, HexError};
 
 const LITERAL_VALUE_TYPE_ID: u8 = 4;
 
@@ -22,62 +24,150 @@ pub struct TransmissionPacket {
     payload: PacketPayload,
 }
 
+impl TransmissionPacket {
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn type_id(&self) -> u8 {
+        self.type_id
+    }
+
+    pub fn payload(&self) -> &PacketPayload {
+        &self.payload
+    }
+}
+
 #[derive(Debug)]
 struct PacketParseResult {
     value: TransmissionPacket,
     bits_read: usize,
 }
 
-#[derive(Debug)]
-struct PacketParseError {}
+#[derive(Debug, PartialEq)]
+pub enum PacketParseError {
+    BitExtraction {
+        start_bit: usize,
+        bit_count: u8,
+        reason: ExtractError,
+    },
+    LiteralOverflow {
+        start_bit: usize,
+    },
+    SubpacketLengthMismatch {
+        start_bit: usize,
+        declared: usize,
+        consumed: usize,
+    },
+    // Only produced by the helpers that both parse and evaluate
+    Evaluation(part_2::EvalError),
+    // Only produced by the helpers that take a hex string
+    InvalidHex(HexError),
+}
+
+impl Display for PacketParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BitExtraction {
+                start_bit,
+                bit_count,
+                reason,
+            } => write!(
+                f,
+                "Could not read {bit_count} bits starting at bit {start_bit}: {reason}"
+            ),
+            Self::LiteralOverflow { start_bit } => write!(
+                f,
+                "Literal value starting at bit {start_bit} does not fit in 64 bits"
+            ),
+            Self::SubpacketLengthMismatch {
+                start_bit,
+                declared,
+                consumed,
+            } => write!(
+                f,
+                "Operator payload starting at bit {start_bit} declares {declared} bits of \
+                 subpackets but they take up {consumed}"
+            ),
+            Self::Evaluation(e) => write!(f, "{e}"),
+            Self::InvalidHex(e) => write!(f, "Invalid hexadecimal transmission: {e}"),
+        }
+    }
+}
+
+// `extract_bits` with its error turned into a `PacketParseError` recording
+// where the read was attempted
+fn read_bits(bytes: &[u8], start_bit: usize, bit_count: u8) -> Result<u64, PacketParseError> {
+    extract_bits(bytes, start_bit, bit_count).map_err(|reason| PacketParseError::BitExtraction {
+        start_bit,
+        bit_count,
+        reason,
+    })
+}
 
-fn parse_literal_value_payload(bytes: &[u8], start_bit: usize) -> PayloadParseResult {
+fn parse_literal_value_payload(
+    bytes: &[u8],
+    start_bit: usize,
+) -> Result<PayloadParseResult, PacketParseError> {
     let mut value = 0u64;
     let mut groups_remaining = true;
     let mut group_start_bit = start_bit;
     let mut bits_read = 0usize;
     while groups_remaining {
-        let group_bits = extract_bits(bytes, group_start_bit, 5).unwrap();
+        let group_bits = read_bits(bytes, group_start_bit, 5)?;
         bits_read += 5;
         let partial_value = group_bits & 0b01111;
+        // Shifting would push set bits out of the top of the u64
+        if value >> 60 != 0 {
+            return Err(PacketParseError::LiteralOverflow { start_bit });
+        }
         value <<= 4;
         value += partial_value;
         groups_remaining = group_bits >> 4 == 1;
         group_start_bit += 5;
     }
-    PayloadParseResult {
+    Ok(PayloadParseResult {
         value: PacketPayload::LiteralValue(value),
         bits_read,
-    }
+    })
 }
 
-fn parse_operator_payload(bytes: &[u8], start_bit: usize) -> PayloadParseResult {
+fn parse_operator_payload(
+    bytes: &[u8],
+    start_bit: usize,
+) -> Result<PayloadParseResult, PacketParseError> {
     let mut bits_read = 0usize;
-    let mode = extract_bits(bytes, start_bit, 1).unwrap();
+    let mode = read_bits(bytes, start_bit, 1)?;
     bits_read += 1;
     let mut subpackets: Vec<TransmissionPacket> = Vec::new();
 
     if mode == 0 {
-        let subpackets_bit_length = extract_bits(bytes, start_bit + 1, 15).unwrap() as usize;
+        let subpackets_bit_length = read_bits(bytes, start_bit + 1, 15)? as usize;
         bits_read += 15;
         let mut subpacket_bits_read = 0usize;
         while subpacket_bits_read < subpackets_bit_length {
             let result =
-                parse_transmission_packet(bytes, start_bit + bits_read + subpacket_bits_read)
-                    .unwrap();
+                parse_transmission_packet(bytes, start_bit + bits_read + subpacket_bits_read)?;
             subpackets.push(result.value);
             subpacket_bits_read += result.bits_read;
         }
+        // The last subpacket may run past the declared length
+        if subpacket_bits_read != subpackets_bit_length {
+            return Err(PacketParseError::SubpacketLengthMismatch {
+                start_bit,
+                declared: subpackets_bit_length,
+                consumed: subpacket_bits_read,
+            });
+        }
         bits_read += subpacket_bits_read;
     } else {
-        let subpacket_count = extract_bits(bytes, start_bit + 1, 11).unwrap() as usize;
+        let subpacket_count = read_bits(bytes, start_bit + 1, 11)? as usize;
         bits_read += 11;
         let mut subpackets_read = 0usize;
         let mut subpacket_bits_read = 0usize;
         while subpackets_read < subpacket_count {
             let result =
-                parse_transmission_packet(bytes, start_bit + bits_read + subpacket_bits_read)
-                    .unwrap();
+                parse_transmission_packet(bytes, start_bit + bits_read + subpacket_bits_read)?;
             subpackets.push(result.value);
             subpackets_read += 1;
             subpacket_bits_read += result.bits_read;
@@ -85,23 +175,23 @@ fn parse_operator_payload(bytes: &[u8], start_bit: usize) -> PayloadParseResult
         bits_read += subpacket_bits_read;
     }
 
-    PayloadParseResult {
+    Ok(PayloadParseResult {
         value: PacketPayload::Operator(subpackets),
         bits_read,
-    }
+    })
 }
 
 fn parse_transmission_packet(
     bytes: &[u8],
     start_bit: usize,
 ) -> Result<PacketParseResult, PacketParseError> {
-    let version = extract_bits(&bytes, start_bit, 3).unwrap() as u8;
-    let type_id = extract_bits(&bytes, start_bit + 3, 3).unwrap() as u8;
+    let version = read_bits(bytes, start_bit, 3)? as u8;
+    let type_id = read_bits(bytes, start_bit + 3, 3)? as u8;
 
     let bits_read = 6usize;
 
     if type_id == LITERAL_VALUE_TYPE_ID {
-        let result = parse_literal_value_payload(bytes, start_bit + 6);
+        let result = parse_literal_value_payload(bytes, start_bit + 6)?;
         return Ok(PacketParseResult {
             value: TransmissionPacket {
                 version,
@@ -112,7 +202,12 @@ fn parse_transmission_packet(
         });
     }
 
-    let result = parse_operator_payload(bytes, start_bit + 6);
+    // A 3-bit type id other than the literal one always names an operation
+    debug_assert!(
+        part_2::Operation::try_from(type_id).is_ok(),
+        "Type id {type_id} does not name an operation"
+    );
+    let result = parse_operator_payload(bytes, start_bit + 6)?;
     Ok(PacketParseResult {
         value: TransmissionPacket {
             version,
@@ -123,21 +218,120 @@ fn parse_transmission_packet(
     })
 }
 
-mod part_1 {
-    use super::{PacketPayload, TransmissionPacket};
+// Parses the packet at the start of `bytes`, also returning how many bits it
+// took up; anything after that should only be zero padding
+pub fn parse_packet(bytes: &[u8]) -> Result<(TransmissionPacket, usize), PacketParseError> {
+    let result = parse_transmission_packet(bytes, 0)?;
+    Ok((result.value, result.bits_read))
+}
 
-    pub fn add_all_packet_versions(packet: &TransmissionPacket) -> u64 {
-        match &packet.payload {
-            PacketPayload::LiteralValue(_) => packet.version as u64,
-            PacketPayload::Operator(subpackets) => {
-                let mut subpacket_sum = packet.version as u64;
-                for subpacket in subpackets {
-                    subpacket_sum += add_all_packet_versions(&subpacket);
-                }
-                return subpacket_sum;
+const HEADER_BIT_LENGTH: usize = 6;
+
+// Checks what's left of the byte holding start_bit with a mask, and the
+// whole bytes after it directly
+fn only_zero_bits_from(bytes: &[u8], start_bit: usize) -> bool {
+    let byte_index = start_bit / 8;
+    match bytes.get(byte_index) {
+        None => true,
+        Some(&byte) => {
+            let remaining_bits_mask = 0xFFu8 >> (start_bit % 8);
+            byte & remaining_bits_mask == 0 && bytes[byte_index + 1..].iter().all(|&b| b == 0)
+        }
+    }
+}
+
+// Packets laid back to back, stopping once what's left can't hold another
+// header or is only zero padding
+pub fn parse_all_packets(bytes: &[u8]) -> Result<Vec<TransmissionPacket>, PacketParseError> {
+    let mut packets = Vec::new();
+    let mut start_bit = 0usize;
+    while start_bit + HEADER_BIT_LENGTH <= bytes.len() * 8 && !only_zero_bits_from(bytes, start_bit)
+    {
+        let result = parse_transmission_packet(bytes, start_bit)?;
+        packets.push(result.value);
+        start_bit += result.bits_read;
+    }
+    Ok(packets)
+}
+
+// Depth-first, parents before their subpackets
+struct PacketsIter<'a> {
+    stack: Vec<&'a TransmissionPacket>,
+}
+
+impl<'a> Iterator for PacketsIter<'a> {
+    type Item = &'a TransmissionPacket;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let packet = self.stack.pop()?;
+        if let PacketPayload::Operator(subpackets) = &packet.payload {
+            self.stack.extend(subpackets.iter().rev());
+        }
+        Some(packet)
+    }
+}
+
+pub fn iter_packets(root: &TransmissionPacket) -> impl Iterator<Item = &TransmissionPacket> {
+    PacketsIter { stack: vec![root] }
+}
+
+fn format_subtree(packet: &TransmissionPacket, depth: usize, output: &mut String) {
+    let indent = "  ".repeat(depth);
+    match &packet.payload {
+        PacketPayload::LiteralValue(value) => {
+            output.push_str(&format!("{indent}Literal v{}: {value}\n", packet.version));
+        }
+        PacketPayload::Operator(subpackets) => {
+            let name = match part_2::Operation::try_from(packet.type_id) {
+                Ok(operation) => format!("{operation:?}"),
+                Err(_) => format!("Unknown({})", packet.type_id),
+            };
+            output.push_str(&format!("{indent}{name} v{}\n", packet.version));
+            for subpacket in subpackets {
+                format_subtree(subpacket, depth + 1, output);
             }
         }
     }
+}
+
+// One line per packet, subpackets indented under their operator
+pub fn format_tree(packet: &TransmissionPacket) -> String {
+    let mut output = String::new();
+    format_subtree(packet, 0, &mut output);
+    output
+}
+
+// How many packets of each type id appear in the tree
+pub fn packet_type_histogram(root: &TransmissionPacket) -> HashMap<u8, usize> {
+    let mut histogram = HashMap::new();
+    for packet in iter_packets(root) {
+        *histogram.entry(packet.type_id).or_insert(0) += 1;
+    }
+    histogram
+}
+
+fn parse_hex_packet(hex: &str) -> Result<TransmissionPacket, PacketParseError> {
+    let bytes = try_hex_str_to_u8_vec(hex.trim_end()).map_err(PacketParseError::InvalidHex)?;
+    let (packet, _) = parse_packet(&bytes)?;
+    Ok(packet)
+}
+
+pub fn version_sum_of_hex(hex: &str) -> Result<u64, PacketParseError> {
+    let packet = parse_hex_packet(hex)?;
+    Ok(part_1::add_all_packet_versions(&packet))
+}
+
+pub fn evaluate_hex(hex: &str) -> Result<u64, PacketParseError> {
+    let packet = parse_hex_packet(hex)?;
+    part_2::evaluate_packet(&packet).map_err(PacketParseError::Evaluation)
+}
+
+mod part_1 {
+    use super::{iter_packets, TransmissionPacket};
+
+    pub fn add_all_packet_versions(packet: &TransmissionPacket) -> u64 {
+        iter_packets(packet).map(|p| p.version as u64).sum()
+    }
 
     #[cfg(test)]
     mod tests {
@@ -225,9 +419,10 @@ mod part_1 {
 
 mod part_2 {
     use super::{PacketPayload, TransmissionPacket};
+    use std::fmt::Display;
 
     #[derive(Debug, PartialEq)]
-    enum Operation {
+    pub enum Operation {
         Sum,
         Product,
         Minimum,
@@ -253,47 +448,92 @@ mod part_2 {
         }
     }
 
-    pub fn evaluate_packet(packet: &TransmissionPacket) -> i64 {
-        match &packet.payload {
-            PacketPayload::LiteralValue(v) => *v as i64,
+    #[derive(Debug, PartialEq)]
+    pub enum EvalError {
+        InvalidOperation(u8),
+        MissingSubpackets { type_id: u8 },
+        WrongComparisonOperandCount { type_id: u8, found: usize },
+        Overflow { type_id: u8 },
+    }
+
+    impl Display for EvalError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::InvalidOperation(type_id) => {
+                    write!(f, "Type id {type_id} does not designate a valid operation")
+                }
+                Self::MissingSubpackets { type_id } => {
+                    write!(f, "Operator packet of type {type_id} has no subpackets")
+                }
+                Self::WrongComparisonOperandCount { type_id, found } => write!(
+                    f,
+                    "Comparison packet of type {type_id} has {found} subpackets; expected 2"
+                ),
+                Self::Overflow { type_id } => {
+                    write!(f, "Operator packet of type {type_id} overflows a u64")
+                }
+            }
+        }
+    }
+
+    // The comparison operators always have exactly two subpackets
+    fn comparison_operands(operands: &[u64], type_id: u8) -> Result<(u64, u64), EvalError> {
+        match operands {
+            [lhs, rhs] => Ok((*lhs, *rhs)),
+            _ => Err(EvalError::WrongComparisonOperandCount {
+                type_id,
+                found: operands.len(),
+            }),
+        }
+    }
+
+    pub fn evaluate_packet(packet: &TransmissionPacket) -> Result<u64, EvalError> {
+        evaluate_packet_traced(packet, &mut |_, _| {})
+    }
+
+    // Same as `evaluate_packet`, handing every packet and its value to `trace`
+    // once its subpackets are done (post-order)
+    pub fn evaluate_packet_traced(
+        packet: &TransmissionPacket,
+        trace: &mut impl FnMut(&TransmissionPacket, u64),
+    ) -> Result<u64, EvalError> {
+        let value = match &packet.payload {
+            PacketPayload::LiteralValue(v) => Ok(*v),
             PacketPayload::Operator(subpackets) => {
-                let operation = Operation::try_from(packet.type_id).unwrap();
-                let mut evaluated_subpackets = subpackets.iter().map(evaluate_packet);
+                let type_id = packet.type_id;
+                let operation = Operation::try_from(type_id)
+                    .map_err(|_| EvalError::InvalidOperation(type_id))?;
+                let operands = subpackets
+                    .iter()
+                    .map(|subpacket| evaluate_packet_traced(subpacket, trace))
+                    .collect::<Result<Vec<u64>, EvalError>>()?;
+                let missing_subpackets = EvalError::MissingSubpackets { type_id };
+                let overflow = || EvalError::Overflow { type_id };
                 match operation {
-                    Operation::Sum => evaluated_subpackets.sum::<i64>(),
-                    Operation::Product => evaluated_subpackets.product::<i64>(),
-                    Operation::Minimum => evaluated_subpackets.min().unwrap(),
-                    Operation::Maximum => evaluated_subpackets.max().unwrap(),
-                    Operation::GreaterThan => {
-                        if evaluated_subpackets.nth(0).unwrap()
-                            > evaluated_subpackets.nth(0).unwrap()
-                        {
-                            1
-                        } else {
-                            0
-                        }
-                    }
-                    Operation::LessThan => {
-                        if evaluated_subpackets.nth(0).unwrap()
-                            < evaluated_subpackets.nth(0).unwrap()
-                        {
-                            1
-                        } else {
-                            0
-                        }
-                    }
-                    Operation::EqualTo => {
-                        if evaluated_subpackets.nth(0).unwrap()
-                            == evaluated_subpackets.nth(0).unwrap()
-                        {
-                            1
-                        } else {
-                            0
-                        }
+                    Operation::Sum => operands
+                        .iter()
+                        .try_fold(0u64, |sum, &operand| sum.checked_add(operand))
+                        .ok_or_else(overflow),
+                    Operation::Product => operands
+                        .iter()
+                        .try_fold(1u64, |product, &operand| product.checked_mul(operand))
+                        .ok_or_else(overflow),
+                    Operation::Minimum => operands.into_iter().min().ok_or(missing_subpackets),
+                    Operation::Maximum => operands.into_iter().max().ok_or(missing_subpackets),
+                    Operation::GreaterThan | Operation::LessThan | Operation::EqualTo => {
+                        let (lhs, rhs) = comparison_operands(&operands, type_id)?;
+                        let holds = match operation {
+                            Operation::GreaterThan => lhs > rhs,
+                            Operation::LessThan => lhs < rhs,
+                            _ => lhs == rhs,
+                        };
+                        Ok(holds as u64)
                     }
                 }
             }
-        }
+        }?;
+        trace(packet, value);
+        Ok(value)
     }
 
     #[cfg(test)]
@@ -331,69 +571,149 @@ mod part_2 {
         #[test]
         fn test_packet_sum_operation() {
             let packet = make_test_packet(0, &[4, 5]);
-            assert_eq!(9, evaluate_packet(&packet));
+            assert_eq!(Ok(9), evaluate_packet(&packet));
         }
 
         #[test]
         fn test_packet_product_operation() {
             let packet = make_test_packet(1, &[4, 5]);
-            assert_eq!(20, evaluate_packet(&packet));
+            assert_eq!(Ok(20), evaluate_packet(&packet));
+        }
+
+        #[test]
+        fn test_packet_product_beyond_i64() {
+            let packet = make_test_packet(1, &[1 << 32, (1 << 31) + 1]);
+            assert_eq!(Ok((1 << 63) + (1 << 32)), evaluate_packet(&packet));
+            assert!(evaluate_packet(&packet).unwrap() > i64::MAX as u64);
+        }
+
+        #[test]
+        fn test_packet_operations_overflowing_u64() {
+            let packet = make_test_packet(1, &[1 << 32, 1 << 32]);
+            assert_eq!(
+                Err(EvalError::Overflow { type_id: 1 }),
+                evaluate_packet(&packet)
+            );
+            let packet = make_test_packet(0, &[u64::MAX, 1]);
+            assert_eq!(
+                Err(EvalError::Overflow { type_id: 0 }),
+                evaluate_packet(&packet)
+            );
+        }
+
+        #[test]
+        fn test_packet_comparison_operations() {
+            assert_eq!(Ok(1), evaluate_packet(&make_test_packet(5, &[15, 5])));
+            assert_eq!(Ok(0), evaluate_packet(&make_test_packet(5, &[5, 15])));
+            assert_eq!(Ok(1), evaluate_packet(&make_test_packet(6, &[5, 15])));
+            assert_eq!(Ok(0), evaluate_packet(&make_test_packet(6, &[15, 5])));
+            assert_eq!(Ok(1), evaluate_packet(&make_test_packet(7, &[5, 5])));
+            assert_eq!(Ok(0), evaluate_packet(&make_test_packet(7, &[5, 15])));
+        }
+
+        #[test]
+        fn test_packet_comparison_with_3_subpackets() {
+            assert_eq!(
+                Err(EvalError::WrongComparisonOperandCount {
+                    type_id: 5,
+                    found: 3
+                }),
+                evaluate_packet(&make_test_packet(5, &[1, 2, 3]))
+            );
+        }
+
+        #[test]
+        fn test_packet_comparison_with_1_subpacket() {
+            assert_eq!(
+                Err(EvalError::WrongComparisonOperandCount {
+                    type_id: 5,
+                    found: 1
+                }),
+                evaluate_packet(&make_test_packet(5, &[1]))
+            );
+        }
+
+        #[test]
+        fn test_packet_minimum_without_subpackets() {
+            assert_eq!(
+                Err(EvalError::MissingSubpackets { type_id: 2 }),
+                evaluate_packet(&make_test_packet(2, &[]))
+            );
+        }
+
+        #[test]
+        fn test_evaluation_trace() {
+            let bytes = hex_str_to_u8_vec("C200B40A82");
+            let packet = parse_transmission_packet(&bytes, 0).unwrap().value;
+            let mut trace = Vec::new();
+            let value = evaluate_packet_traced(&packet, &mut |packet, value| {
+                trace.push((packet.type_id, value))
+            });
+            assert_eq!(Ok(3), value);
+            assert_eq!(
+                vec![
+                    (LITERAL_VALUE_TYPE_ID, 1),
+                    (LITERAL_VALUE_TYPE_ID, 2),
+                    (0, 3)
+                ],
+                trace
+            );
         }
 
         #[test]
         fn test_sum_of_1_and_2() {
             let bytes = hex_str_to_u8_vec("C200B40A82");
             let packet = parse_transmission_packet(&bytes, 0).unwrap().value;
-            assert_eq!(3, evaluate_packet(&packet));
+            assert_eq!(Ok(3), evaluate_packet(&packet));
         }
 
         #[test]
         fn test_product_of_6_and_9() {
             let bytes = hex_str_to_u8_vec("04005AC33890");
             let packet = parse_transmission_packet(&bytes, 0).unwrap().value;
-            assert_eq!(54, evaluate_packet(&packet));
+            assert_eq!(Ok(54), evaluate_packet(&packet));
         }
 
         #[test]
         fn test_minimum_of_7_8_and_9() {
             let bytes = hex_str_to_u8_vec("880086C3E88112");
             let packet = parse_transmission_packet(&bytes, 0).unwrap().value;
-            assert_eq!(7, evaluate_packet(&packet));
+            assert_eq!(Ok(7), evaluate_packet(&packet));
         }
 
         #[test]
         fn test_maximum_of_7_8_and_9() {
             let bytes = hex_str_to_u8_vec("CE00C43D881120");
             let packet = parse_transmission_packet(&bytes, 0).unwrap().value;
-            assert_eq!(9, evaluate_packet(&packet));
+            assert_eq!(Ok(9), evaluate_packet(&packet));
         }
 
         #[test]
         fn test_5_less_than_15() {
             let bytes = hex_str_to_u8_vec("D8005AC2A8F0");
             let packet = parse_transmission_packet(&bytes, 0).unwrap().value;
-            assert_eq!(1, evaluate_packet(&packet));
+            assert_eq!(Ok(1), evaluate_packet(&packet));
         }
 
         #[test]
         fn test_5_not_greater_than_15() {
             let bytes = hex_str_to_u8_vec("F600BC2D8F");
             let packet = parse_transmission_packet(&bytes, 0).unwrap().value;
-            assert_eq!(0, evaluate_packet(&packet));
+            assert_eq!(Ok(0), evaluate_packet(&packet));
         }
 
         #[test]
         fn test_5_not_equal_to_15() {
             let bytes = hex_str_to_u8_vec("9C005AC2F8F0");
             let packet = parse_transmission_packet(&bytes, 0).unwrap().value;
-            assert_eq!(0, evaluate_packet(&packet));
+            assert_eq!(Ok(0), evaluate_packet(&packet));
         }
 
         #[test]
         fn test_sum_of_1_and_3_equal_to_product_of_2_and_2() {
             let bytes = hex_str_to_u8_vec("9C0141080250320F1802104A08");
             let packet = parse_transmission_packet(&bytes, 0).unwrap().value;
-            assert_eq!(1, evaluate_packet(&packet));
+            assert_eq!(Ok(1), evaluate_packet(&packet));
         }
     }
 }
@@ -402,8 +722,12 @@ fn main() {
     let input = read_to_string("data/day-16.txt").unwrap();
     let bytes = hex_str_to_u8_vec(input.trim_end());
 
+    let packet = match parse_packet(&bytes) {
+        Ok((packet, _)) => packet,
+        Err(e) => panic!("Error parsing input file for day 16: {}", e),
+    };
+
     println!("== PART 1");
-    let packet = parse_transmission_packet(&bytes, 0).unwrap().value;
     println!(
         "Sum of version numbers of all packets: {}",
         part_1::add_all_packet_versions(&packet)
@@ -412,10 +736,10 @@ fn main() {
     println!();
 
     println!("== PART 2");
-    println!(
-        "Evaluation of hexadecimal-encoded BITS transmission: {}",
-        part_2::evaluate_packet(&packet)
-    );
+    match part_2::evaluate_packet(&packet) {
+        Ok(value) => println!("Evaluation of hexadecimal-encoded BITS transmission: {value}"),
+        Err(e) => println!("Could not evaluate BITS transmission: {e}"),
+    }
 }
 
 #[cfg(test)]
@@ -634,4 +958,168 @@ mod tests {
         };
         assert_eq!(expected, packet);
     }
+
+    #[test]
+    fn test_parsing_of_truncated_transmission() {
+        // "38006F45291200" cut short in the middle of its second subpacket
+        let bytes = hex_str_to_u8_vec("38006F4529");
+        assert_eq!(
+            Err(PacketParseError::BitExtraction {
+                start_bit: 39,
+                bit_count: 5,
+                reason: ExtractError::NotEnoughBits {
+                    requested: 5,
+                    available: 1,
+                },
+            }),
+            parse_transmission_packet(&bytes, 0).map(|result| result.value)
+        );
+        let bytes = hex_str_to_u8_vec("D2");
+        assert!(parse_transmission_packet(&bytes, 0).is_err());
+    }
+
+    #[test]
+    fn test_parsing_of_literal_value_exceeding_64_bits() {
+        // 20 groups of 0001
+        let bytes = hex_str_to_u8_vec("12318C6318C6318C6318C6318840");
+        assert_eq!(
+            Err(PacketParseError::LiteralOverflow { start_bit: 6 }),
+            parse_transmission_packet(&bytes, 0).map(|result| result.value)
+        );
+        // 20 groups too, but the 17 leading zero groups leave room for the rest
+        let bytes = hex_str_to_u8_vec("1210842108421084210842118840");
+        let packet = parse_transmission_packet(&bytes, 0).unwrap().value;
+        assert_eq!(PacketPayload::LiteralValue(0x111), packet.payload);
+    }
+
+    #[test]
+    fn test_bits_consumed_by_top_level_packet() {
+        let bytes = hex_str_to_u8_vec("D2FE28");
+        let (packet, bits_read) = parse_packet(&bytes).unwrap();
+        assert_eq!(PacketPayload::LiteralValue(2021), packet.payload);
+        assert_eq!(21, bits_read);
+        assert_eq!(Ok(0), extract_bits(&bytes, bits_read, 3));
+    }
+
+    #[test]
+    fn test_iter_packets() {
+        let bytes = hex_str_to_u8_vec("A0016C880162017C3686B18A3D4780");
+        let (packet, _) = parse_packet(&bytes).unwrap();
+        let versions: Vec<u8> = iter_packets(&packet).map(|p| p.version).collect();
+        assert_eq!(vec![5, 1, 3, 7, 6, 5, 2, 2], versions);
+    }
+
+    #[test]
+    fn test_format_tree() {
+        let bytes = hex_str_to_u8_vec("C200B40A82");
+        let (packet, _) = parse_packet(&bytes).unwrap();
+        let tree = format_tree(&packet);
+        assert!(tree.contains("Sum"));
+        assert_eq!("Sum v6\n  Literal v6: 1\n  Literal v2: 2\n", tree);
+    }
+
+    #[test]
+    fn test_parsing_of_operator_with_wrong_subpacket_length() {
+        // Declares 10 bits of subpackets, followed by an 11-bit literal
+        let bytes = hex_str_to_u8_vec("38002B4500");
+        assert_eq!(
+            Err(PacketParseError::SubpacketLengthMismatch {
+                start_bit: 6,
+                declared: 10,
+                consumed: 11
+            }),
+            parse_packet(&bytes).map(|(packet, _)| packet)
+        );
+        // Same packet declaring the right length
+        let bytes = hex_str_to_u8_vec("38002F4500");
+        assert!(parse_packet(&bytes).is_ok());
+    }
+
+    #[test]
+    fn test_version_sum_of_hex() {
+        assert_eq!(Ok(16), version_sum_of_hex("8A004A801A8002F478"));
+        assert_eq!(Ok(31), version_sum_of_hex("A0016C880162017C3686B18A3D4780"));
+        assert!(version_sum_of_hex("D2").is_err());
+        assert_eq!(
+            Err(PacketParseError::InvalidHex(HexError::OddLength(3))),
+            version_sum_of_hex("ABC")
+        );
+        assert_eq!(
+            Err(PacketParseError::InvalidHex(HexError::InvalidDigit {
+                index: 4,
+                found: 'X'
+            })),
+            evaluate_hex("C200X40A82")
+        );
+    }
+
+    #[test]
+    fn test_evaluate_hex() {
+        assert_eq!(Ok(3), evaluate_hex("C200B40A82"));
+        assert_eq!(Ok(0), evaluate_hex("9C005AC2F8F0"));
+        assert_eq!(Ok(1), evaluate_hex("9C0141080250320F1802104A08\n"));
+    }
+
+    #[test]
+    fn test_packet_type_histogram() {
+        let bytes = hex_str_to_u8_vec("620080001611562C8802118E34");
+        let (packet, _) = parse_packet(&bytes).unwrap();
+        let histogram = packet_type_histogram(&packet);
+        assert_eq!(Some(&4), histogram.get(&LITERAL_VALUE_TYPE_ID));
+        assert_eq!(Some(&3), histogram.get(&0));
+        assert_eq!(2, histogram.len());
+    }
+
+    #[test]
+    fn test_parse_all_packets() {
+        // D2FE28's 21 bits followed by an 11-bit literal packet holding 10
+        let bytes = hex_str_to_u8_vec("D2FE2A8A00");
+        let packets = parse_all_packets(&bytes).unwrap();
+        let expected = vec![
+            TransmissionPacket {
+                version: 6,
+                type_id: LITERAL_VALUE_TYPE_ID,
+                payload: PacketPayload::LiteralValue(2021),
+            },
+            TransmissionPacket {
+                version: 2,
+                type_id: LITERAL_VALUE_TYPE_ID,
+                payload: PacketPayload::LiteralValue(10),
+            },
+        ];
+        assert_eq!(expected, packets);
+        assert_eq!(Ok(vec![]), parse_all_packets(&[0, 0]));
+    }
+
+    #[test]
+    fn test_only_zero_bits_from() {
+        // 1101 0010  0000 0000  0000 0001
+        let bytes = [0xD2, 0x00, 0x01];
+        assert!(!only_zero_bits_from(&bytes, 6));
+        assert!(!only_zero_bits_from(&bytes, 7));
+        assert!(!only_zero_bits_from(&bytes, 8));
+        assert!(!only_zero_bits_from(&bytes, 23));
+        assert!(only_zero_bits_from(&bytes, 24));
+        assert!(only_zero_bits_from(&bytes, 30));
+        let bytes = [0xD2, 0x00, 0x00];
+        assert!(!only_zero_bits_from(&bytes, 6));
+        assert!(only_zero_bits_from(&bytes, 7));
+        assert!(only_zero_bits_from(&bytes, 8));
+    }
+}
+
+// Only goes through the public interface, the way code outside this module would
+#[cfg(test)]
+mod public_api_tests {
+    use super::{parse_packet, PacketPayload};
+    use aoc_2021::hex_str_to_u8_vec;
+
+    #[test]
+    fn test_reading_a_parsed_literal_packet() {
+        let bytes = hex_str_to_u8_vec("D2FE28");
+        let (packet, _) = parse_packet(&bytes).unwrap();
+        assert_eq!(6, packet.version());
+        assert_eq!(4, packet.type_id());
+        assert_eq!(&PacketPayload::LiteralValue(2021), packet.payload());
+    }
 }
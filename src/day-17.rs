@@ -17,9 +17,29 @@ pub struct Area {
     p2: Position,
 }
 
+// Corners may be given in any order, so bounds are always read through these
 impl Area {
+    fn min_x(&self) -> i32 {
+        min(self.p1.x, self.p2.x)
+    }
+
+    fn max_x(&self) -> i32 {
+        max(self.p1.x, self.p2.x)
+    }
+
+    fn min_y(&self) -> i32 {
+        min(self.p1.y, self.p2.y)
+    }
+
+    fn max_y(&self) -> i32 {
+        max(self.p1.y, self.p2.y)
+    }
+
     fn contains(&self, pos: &Position) -> bool {
-        pos.x >= self.p1.x && pos.x <= self.p2.x && pos.y <= self.p1.y && pos.y >= self.p2.y
+        pos.x >= self.min_x()
+            && pos.x <= self.max_x()
+            && pos.y >= self.min_y()
+            && pos.y <= self.max_y()
     }
 }
 
@@ -29,35 +49,55 @@ pub enum LaunchResult {
         positions: Vec<Position>,
         launch_velocity: Velocity,
         maximum_y: i32,
-        valid_velocities: usize,
     },
     TargetMissed {
         position: Position,
     },
 }
 
+// The highest reaching hit found by the search, along with the number of
+// distinct launch velocities that hit the target at all
+#[derive(Debug)]
+pub struct LaunchSearchResult {
+    pub positions: Vec<Position>,
+    pub launch_velocity: Velocity,
+    pub maximum_y: i32,
+    pub valid_velocities: usize,
+}
+
 const INITIAL_POSITION: Position = Position { x: 0, y: 0 };
 
-fn parse_input(input: &str) -> Area {
-    let number_pairs: Vec<&str> = input["target area: x=".len()..].split(", y=").collect();
-
-    let numbers: Vec<_> = number_pairs
-        .into_iter()
-        .map(|p| p.split(".."))
-        .flatten()
-        .map(|n| n.parse::<i32>().unwrap())
-        .collect();
-
-    Area {
-        p1: Position {
-            x: numbers[0],
-            y: numbers[3],
-        },
-        p2: Position {
-            x: numbers[1],
-            y: numbers[2],
-        },
-    }
+fn parse_range(range: &str, axis: &str) -> Result<(i32, i32), String> {
+    let bounds = range
+        .strip_prefix(&format!("{axis}="))
+        .ok_or_else(|| format!("Expected {axis}-range, found {range:?}"))?;
+    let (from, to) = bounds
+        .split_once("..")
+        .ok_or_else(|| format!("Malformed {axis}-range {bounds:?}"))?;
+    let parse_bound = |bound: &str| {
+        bound
+            .parse::<i32>()
+            .map_err(|_| format!("Invalid {axis} bound {bound:?}"))
+    };
+    Ok((parse_bound(from)?, parse_bound(to)?))
+}
+
+// Whitespace is dropped up front so any spacing around the ranges is accepted
+fn parse_input(input: &str) -> Result<Area, String> {
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let ranges = compact
+        .strip_prefix("targetarea:")
+        .ok_or_else(|| format!("Missing \"target area:\" prefix in {input:?}"))?;
+    let (x_range, y_range) = ranges
+        .split_once(',')
+        .ok_or_else(|| format!("Missing y-range in {input:?}"))?;
+    let (x_from, x_to) = parse_range(x_range, "x")?;
+    let (y_from, y_to) = parse_range(y_range, "y")?;
+
+    Ok(Area {
+        p1: Position { x: x_from, y: y_to },
+        p2: Position { x: x_to, y: y_from },
+    })
 }
 
 fn simulate_launch_step(position: &Position, velocity: &mut Velocity) -> Position {
@@ -75,120 +115,249 @@ fn simulate_launch_step(position: &Position, velocity: &mut Velocity) -> Positio
     new_position
 }
 
-fn simulate_launch(target_area: &Area, mut velocity: Velocity) -> LaunchResult {
+// Once the probe is below the target and no longer climbing, or beside it and
+// not moving towards it, it can never come back
+fn has_passed_target(target_area: &Area, position: &Position, velocity: &Velocity) -> bool {
+    (position.y < target_area.min_y() && velocity.y <= 0)
+        || (position.x > target_area.max_x() && velocity.x >= 0)
+        || (position.x < target_area.min_x() && velocity.x <= 0)
+}
+
+// Positions from the launch until the probe lands in the target or can no
+// longer reach it
+pub fn trajectory(target_area: &Area, mut velocity: Velocity) -> Vec<Position> {
     let mut position = INITIAL_POSITION;
-    let mut positions = Vec::new();
-    positions.push(INITIAL_POSITION);
-    let launch_velocity = velocity.clone();
-    let mut maximum_y = i32::MIN;
-    while position.x <= target_area.p2.x && position.y >= target_area.p2.y {
+    let mut positions = vec![INITIAL_POSITION];
+    while !has_passed_target(target_area, &position, &velocity) {
         position = simulate_launch_step(&position, &mut velocity);
         positions.push(position.clone());
-        if position.y > maximum_y {
-            maximum_y = position.y;
-        }
         if target_area.contains(&position) {
-            return LaunchResult::TargetHit {
-                positions,
-                launch_velocity,
-                maximum_y,
-                valid_velocities: 1,
-            };
+            break;
         }
     }
-    return LaunchResult::TargetMissed { position };
+    positions
+}
+
+fn simulate_launch(target_area: &Area, velocity: Velocity) -> LaunchResult {
+    let launch_velocity = velocity.clone();
+    let positions = trajectory(target_area, velocity);
+    let position = positions.last().unwrap().clone();
+    if !target_area.contains(&position) {
+        return LaunchResult::TargetMissed { position };
+    }
+    // A target above the origin may be reached while the probe is still
+    // climbing, so the peak comes from the launch velocity rather than from the
+    // positions up to the hit
+    let maximum_y = if launch_velocity.y > 0 {
+        launch_velocity.y * (launch_velocity.y + 1) / 2
+    } else {
+        launch_velocity.y
+    };
+    LaunchResult::TargetHit {
+        positions,
+        launch_velocity,
+        maximum_y,
+    }
+}
+
+// With drag, an x velocity of v stalls after covering the triangular number
+// v * (v + 1) / 2, so anything slower never reaches target_min_x. The
+// discriminant and the triangular numbers overflow i32 for large targets, so
+// they're computed in i64
+fn min_x_velocity_to_reach(target_min_x: i32) -> i32 {
+    if target_min_x <= 0 {
+        return 0;
+    }
+    let target_min_x = i64::from(target_min_x);
+    let mut velocity = ((((8 * target_min_x + 1) as f64).sqrt() - 1.0) / 2.0).ceil() as i64;
+    while velocity > 0 && (velocity - 1) * velocity / 2 >= target_min_x {
+        velocity -= 1;
+    }
+    while velocity * (velocity + 1) / 2 < target_min_x {
+        velocity += 1;
+    }
+    velocity as i32
+}
+
+// Any faster in either direction and the probe overshoots the target on its
+// first step, any slower and it stalls short of it. On the way back down the
+// probe passes y = 0 with velocity -(vy + 1), so for vy >= -min_y it skips
+// past the bottom of a target below the origin. Climbing at vy > max_y it
+// overshoots a target above the origin, and on the way down it revisits the
+// same heights, so it misses again. A target spanning y = 0 can also be hit at
+// y = 0 itself, on step 2 * vy + 1. Unless x stalls inside the target (see
+// has_unbounded_height), x is only in range for fewer than max |x| steps
+fn candidate_velocities(target_area: &Area) -> impl Iterator<Item = Velocity> {
+    let min_x = if target_area.min_x() > 0 {
+        min_x_velocity_to_reach(target_area.min_x())
+    } else {
+        target_area.min_x()
+    };
+    let max_x = if target_area.max_x() < 0 {
+        -min_x_velocity_to_reach(-target_area.max_x())
+    } else {
+        target_area.max_x()
+    };
+    let x_velocities = min_x..=max_x;
+    let mut max_y_velocity = max(-target_area.min_y(), target_area.max_y());
+    if target_area.min_y() <= 0 && target_area.max_y() >= 0 {
+        max_y_velocity = max(
+            max_y_velocity,
+            max(target_area.min_x().abs(), target_area.max_x().abs()),
+        );
+    }
+    let y_velocities = min(target_area.min_y(), 0)..=max_y_velocity;
+    y_velocities.flat_map(move |y| x_velocities.clone().map(move |x| Velocity { x, y }))
+}
+
+pub fn all_valid_velocities(target_area: &Area) -> Vec<Velocity> {
+    candidate_velocities(target_area)
+        .filter(|velocity| {
+            matches!(
+                simulate_launch(target_area, velocity.clone()),
+                LaunchResult::TargetHit { .. }
+            )
+        })
+        .collect()
+}
+
+// Launched upwards at vy, the probe comes back down through y = 0 at
+// -(vy + 1), so the highest shot to still land in a target below the origin
+// has vy = -min_y - 1 and peaks at the triangular number of that. This
+// assumes some x velocity stalls within the target's x range. A target above
+// the origin is best hit on the first step with vy = max_y. A target spanning
+// y = 0 has no bound once the probe can stall inside it
+pub fn analytic_max_height(target_area: &Area) -> Option<i32> {
+    let (min_y, max_y) = (target_area.min_y(), target_area.max_y());
+    if min_y > 0 {
+        Some(max_y * (max_y + 1) / 2)
+    } else if max_y < 0 {
+        Some(min_y * (min_y + 1) / 2)
+    } else {
+        None
+    }
+}
+
+// When the target spans y = 0 and some x velocity stalls inside it, every
+// upward shot with that x velocity comes back through y = 0 inside the target,
+// so there's no maximum height and no finite number of valid velocities
+pub fn has_unbounded_height(target_area: &Area) -> bool {
+    if target_area.min_y() > 0 || target_area.max_y() < 0 {
+        return false;
+    }
+    let x_range = target_area.min_x()..=target_area.max_x();
+    let farthest_x = max(target_area.min_x().abs(), target_area.max_x().abs());
+    (0..)
+        .map(|velocity| velocity * (velocity + 1) / 2)
+        .take_while(|&stall_x| stall_x <= farthest_x)
+        .any(|stall_x| x_range.contains(&stall_x) || x_range.contains(&-stall_x))
 }
 
 pub fn find_launch_velocity_that_hits_target_and_reaches_highest_y(
     target_area: &Area,
-) -> LaunchResult {
-    let mut hit_maximum_y = i32::MIN;
-    let mut hit_positions = Vec::new();
-    let mut hit_launch_velocity = Velocity { x: 0, y: 0 };
+) -> Option<LaunchSearchResult> {
+    let mut best_hit: Option<LaunchSearchResult> = None;
     let mut valid_launch_velocities = HashSet::new();
-    for y_velocity in -200..200 {
-        for x_velocity in 1..300 {
-            let velocity = Velocity {
-                x: x_velocity,
-                y: y_velocity,
-            };
-            let result = simulate_launch(target_area, velocity.clone());
-            if let LaunchResult::TargetHit {
-                positions,
-                launch_velocity,
-                maximum_y,
-                valid_velocities: _,
-            } = result
+    for velocity in candidate_velocities(target_area) {
+        let result = simulate_launch(target_area, velocity);
+        if let LaunchResult::TargetHit {
+            positions,
+            launch_velocity,
+            maximum_y,
+        } = result
+        {
+            valid_launch_velocities.insert(launch_velocity.clone());
+            if best_hit
+                .as_ref()
+                .is_none_or(|best_hit| maximum_y > best_hit.maximum_y)
             {
-                valid_launch_velocities.insert(launch_velocity.clone());
-                if maximum_y > hit_maximum_y {
-                    hit_positions = positions;
-                    hit_launch_velocity = launch_velocity;
-                    hit_maximum_y = maximum_y;
-                }
+                best_hit = Some(LaunchSearchResult {
+                    positions,
+                    launch_velocity,
+                    maximum_y,
+                    valid_velocities: 0,
+                });
             }
         }
     }
-    LaunchResult::TargetHit {
-        positions: hit_positions,
-        launch_velocity: hit_launch_velocity,
-        maximum_y: hit_maximum_y,
+    best_hit.map(|best_hit| LaunchSearchResult {
         valid_velocities: valid_launch_velocities.len(),
+        ..best_hit
+    })
+}
+
+// Both part answers from a single search: the maximum height and the number of
+// valid launch velocities. A target that can never be hit has no maximum
+// height and yields (None, 0), and one where launches can go arbitrarily high
+// is an error
+pub fn solve(target_area: &Area) -> Result<(Option<i32>, usize), String> {
+    if has_unbounded_height(target_area) {
+        return Err(format!(
+            "Target {target_area:?} spans y = 0 and the probe can stall inside it, \
+             so there is no maximum height"
+        ));
     }
+    Ok(
+        find_launch_velocity_that_hits_target_and_reaches_highest_y(target_area)
+            .map_or((None, 0), |result| {
+                (Some(result.maximum_y), result.valid_velocities)
+            }),
+    )
+}
+
+const MAX_DIAGRAM_COLUMNS: i32 = 200;
+
+pub fn render_diagram(target_area: &Area, positions: &[Position]) -> String {
+    render_diagram_with_max_columns(target_area, positions, MAX_DIAGRAM_COLUMNS)
 }
 
-fn draw_launch_diagram(target_area: &Area, positions: &Vec<Position>) {
-    let min_y = min(
-        positions
-            .iter()
-            .map(|pos| pos.y)
-            .min_by(|y1, y2| y1.partial_cmp(&y2).unwrap())
-            .unwrap(),
-        target_area.p2.y,
-    );
-    let max_y = max(
-        positions
-            .iter()
-            .map(|pos| pos.y)
-            .min_by(|y1, y2| y2.partial_cmp(&y1).unwrap())
-            .unwrap(),
-        target_area.p1.y,
-    );
-    let min_x = positions[0].x;
-    let max_x = max(
-        positions
-            .iter()
-            .map(|pos| pos.x)
-            .min_by(|x1, x2| x2.partial_cmp(&x1).unwrap())
-            .unwrap(),
-        target_area.p2.x,
-    );
+pub fn render_diagram_with_max_columns(
+    target_area: &Area,
+    positions: &[Position],
+    max_columns: i32,
+) -> String {
+    // Seeding with the target bounds also covers an empty trajectory
+    let min_y = positions
+        .iter()
+        .map(|pos| pos.y)
+        .fold(target_area.min_y(), min);
+    let max_y = positions
+        .iter()
+        .map(|pos| pos.y)
+        .fold(target_area.max_y(), max);
+    let min_x = positions
+        .iter()
+        .map(|pos| pos.x)
+        .fold(target_area.min_x(), min);
+    let max_x = positions
+        .iter()
+        .map(|pos| pos.x)
+        .fold(target_area.max_x(), max);
 
     let cols = max_x - min_x + 1;
-    if cols > 50 {
-        println!("Diagram exceeds maximum size");
-        return;
+    if cols > max_columns {
+        return format!("Diagram exceeds maximum size ({cols} > {max_columns} columns)\n");
     }
 
-    let mut sorted_positions = positions.clone();
-    sorted_positions.sort_by(|pos_1, pos_2| {
-        return pos_2.y.partial_cmp(&pos_1.y).unwrap();
-    });
+    // Top to bottom, left to right, in step with the rendering below
+    let mut sorted_positions = positions.to_vec();
+    sorted_positions.sort_by(|pos_1, pos_2| pos_2.y.cmp(&pos_1.y).then(pos_1.x.cmp(&pos_2.x)));
     sorted_positions.dedup();
 
-    print!("    ");
+    let mut diagram = String::new();
+    diagram.push_str("    ");
     for col in (0..cols).step_by(10) {
-        print!("{:<20}", col / 10);
+        diagram.push_str(&format!("{:<20}", col / 10));
     }
-    println!();
-    print!("    ");
+    diagram.push('\n');
+    diagram.push_str("    ");
     for col in 0..cols {
-        print!("{} ", col % 10);
+        diagram.push_str(&format!("{} ", col % 10));
     }
-    println!();
+    diagram.push('\n');
     let mut pos_idx = 0;
     for row in (min_y..=max_y).rev() {
-        print!("{row:3} ");
+        diagram.push_str(&format!("{row:3} "));
         for col in min_x..=max_x {
             if pos_idx < sorted_positions.len() {
                 let pos = &sorted_positions[pos_idx];
@@ -197,44 +366,43 @@ fn draw_launch_diagram(target_area: &Area, positions: &Vec<Position>) {
                     if pos == &INITIAL_POSITION {
                         probe_char = '\u{25c9}';
                     }
-                    print!("{probe_char} ");
+                    diagram.push_str(&format!("{probe_char} "));
                     pos_idx += 1;
                     continue;
                 }
             }
             if target_area.contains(&Position { x: col, y: row }) {
-                print!("{} ", '\u{25cb}');
+                diagram.push_str("\u{25cb} ");
             } else {
-                print!(". ");
+                diagram.push_str(". ");
             }
         }
-        println!();
+        diagram.push('\n');
     }
+    diagram
 }
 
 fn main() {
     let input = &read_to_string("data/day-17.txt").unwrap();
-    let target_area = parse_input(input.trim());
-    let result = find_launch_velocity_that_hits_target_and_reaches_highest_y(&target_area);
+    let target_area = match parse_input(input.trim()) {
+        Ok(target_area) => target_area,
+        Err(e) => panic!("Error parsing input file for day 17: {}", e),
+    };
+    let (maximum_y, valid_velocities) = match solve(&target_area) {
+        Ok(answers) => answers,
+        Err(e) => panic!("Error solving day 17: {}", e),
+    };
 
-    if let LaunchResult::TargetHit {
-        positions,
-        launch_velocity,
-        maximum_y,
-        valid_velocities,
-    } = result
-    {
-        println!("== PART 1");
-        println!("Maximum y: {}", maximum_y);
-        println!("Launch velocity: {:?}", launch_velocity);
-
-        println!();
+    println!("== PART 1");
+    match maximum_y {
+        Some(maximum_y) => println!("Maximum y: {}", maximum_y),
+        None => println!("No launch velocity hits the target"),
+    }
 
-        println!("== PART 2");
-        println!("Valid velocities that hit the target: {valid_velocities}");
+    println!();
 
-        //draw_launch_diagram(&target_area, &positions);
-    }
+    println!("== PART 2");
+    println!("Valid velocities that hit the target: {valid_velocities}");
 }
 
 #[cfg(test)]
@@ -249,7 +417,28 @@ mod tests {
             p1: Position { x: -5, y: 10 },
             p2: Position { x: 2, y: 0 },
         };
-        assert_eq!(expected, area);
+        assert_eq!(Ok(expected), area);
+    }
+
+    #[test]
+    fn test_input_parsing_with_extra_spaces() {
+        let input = "target area:  x = 20 .. 30 ,  y = -10 .. -5 ";
+        let expected = Area {
+            p1: Position { x: 20, y: -5 },
+            p2: Position { x: 30, y: -10 },
+        };
+        assert_eq!(Ok(expected), parse_input(input));
+    }
+
+    #[test]
+    fn test_input_parsing_with_missing_y_range() {
+        let input = "target area: x=20..30";
+        assert_eq!(
+            Err(String::from("Missing y-range in \"target area: x=20..30\"")),
+            parse_input(input)
+        );
+        assert!(parse_input("target area: x=20..30, y=-10").is_err());
+        assert!(parse_input("x=20..30, y=-10..-5").is_err());
     }
 
     #[test]
@@ -264,4 +453,215 @@ mod tests {
         assert!(!&area.contains(&Position { x: -6, y: 5 }));
         assert!(!&area.contains(&Position { x: 0, y: -1 }));
     }
+
+    #[test]
+    fn test_area_contains_with_swapped_corners() {
+        let area = Area {
+            p1: Position { x: 2, y: 0 },
+            p2: Position { x: -5, y: 10 },
+        };
+        assert!(&area.contains(&Position { x: -1, y: 2 }));
+        assert!(&area.contains(&Position { x: -5, y: 10 }));
+        assert!(&area.contains(&Position { x: 2, y: 0 }));
+        assert!(!&area.contains(&Position { x: -6, y: 5 }));
+        assert!(!&area.contains(&Position { x: 0, y: -1 }));
+        let area = Area {
+            p1: Position { x: -5, y: 0 },
+            p2: Position { x: 2, y: 10 },
+        };
+        assert!(&area.contains(&Position { x: -1, y: 2 }));
+        assert!(!&area.contains(&Position { x: 0, y: 11 }));
+    }
+
+    #[test]
+    fn test_far_target_is_hit() {
+        let target_area = parse_input("target area: x=400..420, y=-10..-5").unwrap();
+        let result = find_launch_velocity_that_hits_target_and_reaches_highest_y(&target_area)
+            .expect("Far target should be hit");
+        // x = 28 stalls at 406, leaving the y velocity free to go as high as 9
+        assert_eq!(45, result.maximum_y);
+        assert!(result.valid_velocities > 0);
+    }
+
+    #[test]
+    fn test_target_to_the_left_is_hit() {
+        let target_area = parse_input("target area: x=-30..-20, y=-10..-5").unwrap();
+        let result = find_launch_velocity_that_hits_target_and_reaches_highest_y(&target_area)
+            .expect("Target to the left should be hit");
+        // Mirror image of the AoC example
+        assert!(result.launch_velocity.x < 0);
+        assert_eq!(45, result.maximum_y);
+        assert_eq!(112, result.valid_velocities);
+    }
+
+    #[test]
+    fn test_all_valid_velocities() {
+        let target_area = parse_input("target area: x=20..30, y=-10..-5").unwrap();
+        let velocities = all_valid_velocities(&target_area);
+        assert_eq!(112, velocities.len());
+        assert!(velocities.contains(&Velocity { x: 23, y: -10 }));
+        assert!(velocities.contains(&Velocity { x: 6, y: 9 }));
+        assert!(velocities.contains(&Velocity { x: 7, y: -1 }));
+        assert!(velocities.contains(&Velocity { x: 30, y: -5 }));
+        assert!(!velocities.contains(&Velocity { x: 17, y: -4 }));
+    }
+
+    #[test]
+    fn test_probe_falling_through_target_after_x_stalls() {
+        let target_area = parse_input("target area: x=20..30, y=-10..-5").unwrap();
+        // x stalls at 6 + 5 + 4 + 3 + 2 + 1 = 21 while the probe is still climbing
+        match simulate_launch(&target_area, Velocity { x: 6, y: 3 }) {
+            LaunchResult::TargetHit { positions, .. } => {
+                assert_eq!(Some(&Position { x: 21, y: -9 }), positions.last());
+                assert_eq!(Position { x: 21, y: 3 }, positions[6]);
+            }
+            LaunchResult::TargetMissed { .. } => panic!("Stalled probe should fall into target"),
+        }
+        // Stalls at 15, short of the target
+        match simulate_launch(&target_area, Velocity { x: 5, y: 3 }) {
+            LaunchResult::TargetMissed { position } => assert_eq!(15, position.x),
+            LaunchResult::TargetHit { .. } => panic!("Probe stalling at x = 15 can't hit"),
+        }
+    }
+
+    #[test]
+    fn test_analytic_max_height() {
+        let target_area = parse_input("target area: x=20..30, y=-10..-5").unwrap();
+        let result = find_launch_velocity_that_hits_target_and_reaches_highest_y(&target_area)
+            .expect("AoC example target should be hit");
+        assert_eq!(45, result.maximum_y);
+        assert_eq!(Some(result.maximum_y), analytic_max_height(&target_area));
+        let target_area = parse_input("target area: x=20..30, y=5..15").unwrap();
+        assert_eq!(Some(120), analytic_max_height(&target_area));
+        let target_area = parse_input("target area: x=20..30, y=-5..5").unwrap();
+        assert_eq!(None, analytic_max_height(&target_area));
+    }
+
+    #[test]
+    fn test_trajectory() {
+        let target_area = parse_input("target area: x=20..30, y=-10..-5").unwrap();
+        let positions = trajectory(&target_area, Velocity { x: 7, y: 2 });
+        assert_eq!(Some(&INITIAL_POSITION), positions.first());
+        assert_eq!(Some(&Position { x: 28, y: -7 }), positions.last());
+        assert!(positions.iter().any(|pos| target_area.contains(pos)));
+        // Misses, overshooting to the right
+        let positions = trajectory(&target_area, Velocity { x: 17, y: -4 });
+        assert_eq!(Some(&Position { x: 33, y: -9 }), positions.last());
+        assert!(!positions.iter().any(|pos| target_area.contains(pos)));
+    }
+
+    #[test]
+    fn test_render_diagram() {
+        let target_area = parse_input("target area: x=20..30, y=-10..-5").unwrap();
+        let positions = trajectory(&target_area, Velocity { x: 7, y: 2 });
+        let diagram = render_diagram(&target_area, &positions);
+        assert!(diagram.contains('\u{25c9}'));
+        assert!(diagram.contains('\u{25cb}'));
+        assert_eq!(7, diagram.matches('\u{25ce}').count());
+        // Two header lines and one line per row from y = 3 down to y = -10
+        assert_eq!(2 + 14, diagram.lines().count());
+        assert!(diagram.lines().nth(2).unwrap().starts_with("  3 "));
+
+        let diagram = render_diagram_with_max_columns(&target_area, &positions, 10);
+        assert_eq!("Diagram exceeds maximum size (31 > 10 columns)\n", diagram);
+    }
+
+    #[test]
+    fn test_render_diagram_of_an_empty_trajectory() {
+        let target_area = parse_input("target area: x=20..30, y=-10..-5").unwrap();
+        let diagram = render_diagram(&target_area, &[]);
+        assert!(!diagram.contains('\u{25ce}'));
+        assert_eq!(66, diagram.matches('\u{25cb}').count());
+        // Two header lines and one line per row of the target area
+        assert_eq!(2 + 6, diagram.lines().count());
+    }
+
+    #[test]
+    fn test_aggregated_valid_velocities() {
+        let target_area = parse_input("target area: x=20..30, y=-10..-5").unwrap();
+        let result = find_launch_velocity_that_hits_target_and_reaches_highest_y(&target_area)
+            .expect("AoC example target should be hit");
+        assert_eq!(
+            all_valid_velocities(&target_area).len(),
+            result.valid_velocities
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        let target_area = parse_input("target area: x=20..30, y=-10..-5").unwrap();
+        assert_eq!(Ok((Some(45), 112)), solve(&target_area));
+    }
+
+    #[test]
+    fn test_simulate_launch_step_x_drag() {
+        let position = Position { x: 5, y: 5 };
+
+        let mut velocity = Velocity { x: 3, y: 2 };
+        let new_position = simulate_launch_step(&position, &mut velocity);
+        assert_eq!(Position { x: 8, y: 7 }, new_position);
+        assert_eq!(Velocity { x: 2, y: 1 }, velocity);
+
+        let mut velocity = Velocity { x: -3, y: 2 };
+        let new_position = simulate_launch_step(&position, &mut velocity);
+        assert_eq!(Position { x: 2, y: 7 }, new_position);
+        assert_eq!(Velocity { x: -2, y: 1 }, velocity);
+
+        let mut velocity = Velocity { x: 0, y: 2 };
+        let new_position = simulate_launch_step(&position, &mut velocity);
+        assert_eq!(Position { x: 5, y: 7 }, new_position);
+        assert_eq!(Velocity { x: 0, y: 1 }, velocity);
+    }
+
+    #[test]
+    fn test_min_x_velocity_to_reach() {
+        // 5 stalls at 15, 6 stalls at 21
+        assert_eq!(6, min_x_velocity_to_reach(20));
+        assert_eq!(6, min_x_velocity_to_reach(21));
+        assert_eq!(7, min_x_velocity_to_reach(22));
+        assert_eq!(1, min_x_velocity_to_reach(1));
+        assert_eq!(0, min_x_velocity_to_reach(0));
+        // 65535 stalls at 2147450880, 65536 at 2147516416
+        assert_eq!(65536, min_x_velocity_to_reach(i32::MAX));
+
+        let target_area = parse_input("target area: x=20..30, y=-10..-5").unwrap();
+        assert!(candidate_velocities(&target_area).all(|velocity| velocity.x >= 6));
+        assert_eq!(112, all_valid_velocities(&target_area).len());
+    }
+
+    #[test]
+    fn test_target_above_origin() {
+        let target_area = parse_input("target area: x=20..30, y=5..15").unwrap();
+        // Hit on the first step while still climbing at 15, peaking at 120
+        assert_eq!(Ok((Some(120), 189)), solve(&target_area));
+        assert_eq!(189, all_valid_velocities(&target_area).len());
+    }
+
+    #[test]
+    fn test_search_with_swapped_corners() {
+        let target_area = Area {
+            p1: Position { x: 30, y: -10 },
+            p2: Position { x: 20, y: -5 },
+        };
+        assert_eq!(Ok((Some(45), 112)), solve(&target_area));
+        assert_eq!(Some(45), analytic_max_height(&target_area));
+        let target_area = parse_input("target area: x=30..20, y=-5..-10").unwrap();
+        assert_eq!(Ok((Some(45), 112)), solve(&target_area));
+    }
+
+    #[test]
+    fn test_target_spanning_y_zero() {
+        // x = 6 stalls at 21, so every upward shot comes back through (21, 0)
+        let target_area = parse_input("target area: x=20..30, y=-5..5").unwrap();
+        assert!(has_unbounded_height(&target_area));
+        assert_eq!(None, analytic_max_height(&target_area));
+        assert!(solve(&target_area).is_err());
+        // Stalling at 21 or 28 misses 22..27, so shots are bounded. vy = 2 is
+        // back at y = 0 on step 5, while x = 7 is passing through at 25
+        let target_area = parse_input("target area: x=22..27, y=-1..1").unwrap();
+        assert!(!has_unbounded_height(&target_area));
+        assert_eq!(Ok((Some(3), 27)), solve(&target_area));
+        let target_area = parse_input("target area: x=22..27, y=-3..2").unwrap();
+        assert_eq!(Ok((Some(3), 53)), solve(&target_area));
+    }
 }
@@ -1,7 +1,17 @@
 use std::cmp::min;
+use std::fmt;
+use std::io::Read;
 
 pub fn parse_input<T: std::str::FromStr>(path: &str) -> Result<Vec<T>, std::io::Error> {
-    Ok(std::fs::read_to_string(path)?
+    parse_reader(std::fs::File::open(path)?)
+}
+
+pub fn parse_reader<T: std::str::FromStr, R: Read>(
+    mut reader: R,
+) -> Result<Vec<T>, std::io::Error> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    Ok(input
         .split("\n")
         .filter_map(|line| {
             if line.trim().is_empty() {
@@ -12,6 +22,47 @@ pub fn parse_input<T: std::str::FromStr>(path: &str) -> Result<Vec<T>, std::io::
         .collect::<Vec<T>>())
 }
 
+#[derive(Debug)]
+pub enum ParseInputError {
+    Io(std::io::Error),
+    InvalidLine { line_number: usize, line: String },
+}
+
+impl fmt::Display for ParseInputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseInputError::Io(e) => write!(f, "{}", e),
+            ParseInputError::InvalidLine { line_number, line } => {
+                write!(f, "Invalid value {:?} at line {}", line, line_number)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseInputError {}
+
+impl From<std::io::Error> for ParseInputError {
+    fn from(e: std::io::Error) -> Self {
+        ParseInputError::Io(e)
+    }
+}
+
+// Same as parse_input, but a line that fails to parse is reported along with
+// its 1-based line number instead of being dropped
+pub fn parse_input_strict<T: std::str::FromStr>(path: &str) -> Result<Vec<T>, ParseInputError> {
+    std::fs::read_to_string(path)?
+        .split("\n")
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            line.parse::<T>().map_err(|_| ParseInputError::InvalidLine {
+                line_number: index + 1,
+                line: line.to_string(),
+            })
+        })
+        .collect()
+}
+
 pub fn bit_str_to_u64(bit_str: &str) -> u64 {
     let bit_str = bit_str.trim();
     if bit_str.len() == 0 {
@@ -28,6 +79,16 @@ pub fn bit_str_to_u64(bit_str: &str) -> u64 {
     value
 }
 
+// Like bit_str_to_u64, but anything other than '0' or '1' once the ends are
+// trimmed is returned as the error instead of being read as a zero
+pub fn bit_str_to_u64_checked(bit_str: &str) -> Result<u64, char> {
+    bit_str.trim().chars().try_fold(0u64, |value, b| match b {
+        '0' => Ok(value << 1),
+        '1' => Ok((value << 1) | 1),
+        _ => Err(b),
+    })
+}
+
 pub fn hex_str_to_u8_vec(input: &str) -> Vec<u8> {
     (0..input.len())
         .step_by(2)
@@ -40,33 +101,129 @@ pub fn hex_str_to_u8_vec(input: &str) -> Vec<u8> {
         .collect()
 }
 
+#[derive(Debug, PartialEq)]
+pub enum HexError {
+    OddLength(usize),
+    InvalidDigit { index: usize, found: char },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HexError::OddLength(length) => {
+                write!(f, "hex string has an odd length of {}", length)
+            }
+            HexError::InvalidDigit { index, found } => {
+                write!(f, "invalid hex digit {:?} at index {}", found, index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HexError {}
+
+// Fallible counterpart of hex_str_to_u8_vec
+pub fn try_hex_str_to_u8_vec(input: &str) -> Result<Vec<u8>, HexError> {
+    let digits = input
+        .chars()
+        .enumerate()
+        .map(|(index, found)| {
+            found
+                .to_digit(16)
+                .map(|digit| digit as u8)
+                .ok_or(HexError::InvalidDigit { index, found })
+        })
+        .collect::<Result<Vec<u8>, HexError>>()?;
+    if digits.len() % 2 != 0 {
+        return Err(HexError::OddLength(digits.len()));
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair[1])
+        .collect())
+}
+
+pub fn u8_vec_to_hex_str(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
 pub fn print_type_of<T>(_: &T) -> String {
     format!("{}", std::any::type_name::<T>())
 }
 
+// start_bit is taken modulo 8, and the bits must not run past the end of the
+// byte: 0 < bit_count <= 8 - start_bit % 8. Panics otherwise, as the shift
+// below would be meaningless
 pub fn extract_bits_from_byte(byte_value: u8, start_bit: u8, bit_count: u8) -> u8 {
     let bit_offset = start_bit % 8;
+    assert!(
+        bit_count > 0 && bit_offset + bit_count <= 8,
+        "cannot extract {} bits from bit offset {} of a byte",
+        bit_count,
+        bit_offset
+    );
     let mut extracted = byte_value;
 
     if bit_offset > 0 {
-        let left_trim = (1 << 8 - bit_offset) - 1;
+        let left_trim = (1 << (8 - bit_offset)) - 1;
         extracted &= left_trim;
     }
 
-    extracted >> 8 - bit_count - bit_offset
+    extracted >> (8 - bit_count - bit_offset)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ExtractError {
+    StartBitOutOfRange { start_bit: usize, total_bits: usize },
+    BitCountTooLarge(u8),
+    NotEnoughBits { requested: u8, available: usize },
 }
 
-pub fn extract_bits(bytes: &[u8], start_bit: usize, bit_count: u8) -> Result<u64, &str> {
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExtractError::StartBitOutOfRange {
+                start_bit,
+                total_bits,
+            } => write!(
+                f,
+                "start bit {} is out of range for {} bits",
+                start_bit, total_bits
+            ),
+            ExtractError::BitCountTooLarge(bit_count) => {
+                write!(f, "cannot extract {} bits, the maximum is 64", bit_count)
+            }
+            ExtractError::NotEnoughBits {
+                requested,
+                available,
+            } => write!(
+                f,
+                "requested {} bits but only {} are available",
+                requested, available
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+pub fn extract_bits(bytes: &[u8], start_bit: usize, bit_count: u8) -> Result<u64, ExtractError> {
     let total_bits = bytes.len() * 8;
 
     if start_bit >= total_bits {
-        return Err("invalid start_bit");
+        return Err(ExtractError::StartBitOutOfRange {
+            start_bit,
+            total_bits,
+        });
     }
     if bit_count > 64 {
-        return Err("max value for bit_count is 64");
+        return Err(ExtractError::BitCountTooLarge(bit_count));
     }
     if start_bit + bit_count as usize > total_bits {
-        return Err("not enough bits to extract");
+        return Err(ExtractError::NotEnoughBits {
+            requested: bit_count,
+            available: total_bits - start_bit,
+        });
     }
 
     let mut byte_index = start_bit / 8;
@@ -88,10 +245,242 @@ pub fn extract_bits(bytes: &[u8], start_bit: usize, bit_count: u8) -> Result<u64
     Ok(extracted)
 }
 
+// Treats the highest extracted bit as the sign bit of a two's complement value
+pub fn extract_bits_signed(
+    bytes: &[u8],
+    start_bit: usize,
+    bit_count: u8,
+) -> Result<i64, ExtractError> {
+    let value = extract_bits(bytes, start_bit, bit_count)?;
+    if bit_count == 0 {
+        return Ok(0);
+    }
+    let shift = 64 - u32::from(bit_count);
+    Ok(((value << shift) as i64) >> shift)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BitError {
+    pub position: usize,
+    pub bit_count: u8,
+    pub reason: ExtractError,
+}
+
+impl fmt::Display for BitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Could not read {} bits at position {}: {}",
+            self.bit_count, self.position, self.reason
+        )
+    }
+}
+
+impl std::error::Error for BitError {}
+
+// Reads consecutive bit fields from a byte slice, keeping track of the
+// position so callers don't have to
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        BitReader { bytes, cursor: 0 }
+    }
+
+    // The cursor only advances when the read succeeds
+    pub fn read(&mut self, bit_count: u8) -> Result<u64, BitError> {
+        let value =
+            extract_bits(self.bytes, self.cursor, bit_count).map_err(|reason| BitError {
+                position: self.cursor,
+                bit_count,
+                reason,
+            })?;
+        self.cursor += bit_count as usize;
+        Ok(value)
+    }
+
+    pub fn position(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() * 8 - self.cursor
+    }
+}
+
+const ORTHOGONAL_OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const ALL_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+// A rectangular grid stored row by row in a single Vec
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    // Cells are given row by row. Panics unless they fill whole rows of width
+    pub fn from_vec(width: usize, cells: Vec<T>) -> Self {
+        assert!(
+            width > 0 && cells.len().is_multiple_of(width),
+            "{} cells do not fill rows of width {}",
+            cells.len(),
+            width
+        );
+        let height = cells.len() / width;
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row >= self.height || col >= self.width {
+            return None;
+        }
+        self.cells.get(row * self.width + col)
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if row >= self.height || col >= self.width {
+            return None;
+        }
+        self.cells.get_mut(row * self.width + col)
+    }
+
+    fn neighbors(
+        &self,
+        row: usize,
+        col: usize,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width, self.height);
+        offsets.iter().filter_map(move |&(row_offset, col_offset)| {
+            let neighbor_row = row.checked_add_signed(row_offset)?;
+            let neighbor_col = col.checked_add_signed(col_offset)?;
+            (neighbor_row < height && neighbor_col < width).then_some((neighbor_row, neighbor_col))
+        })
+    }
+
+    // Positions above, below, left and right of (row, col) that are inside the grid
+    pub fn orthogonal_neighbors(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        self.neighbors(row, col, &ORTHOGONAL_OFFSETS)
+    }
+
+    // Same as orthogonal_neighbors, but including diagonals
+    pub fn all_neighbors(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> {
+        self.neighbors(row, col, &ALL_OFFSETS)
+    }
+}
+
+impl Grid<u8> {
+    // One row per line, one digit per cell, as in the day-09 and day-11
+    // inputs. Panics on a non-digit character or rows of different lengths
+    pub fn from_digit_lines(input: &str) -> Grid<u8> {
+        let mut cells: Vec<u8> = Vec::new();
+        let mut width = 0;
+        let mut height = 0;
+        for (row, line) in input.split_terminator("\n").enumerate() {
+            let row_start = cells.len();
+            for (col, c) in line.chars().enumerate() {
+                match c.to_digit(10) {
+                    Some(digit) => cells.push(digit as u8),
+                    None => panic!("Invalid digit {:?} at row {}, col {}", c, row, col),
+                }
+            }
+            let row_width = cells.len() - row_start;
+            if row == 0 {
+                width = row_width;
+            } else if row_width != width {
+                panic!("Row {} has {} cells; expected {}", row, row_width, width);
+            }
+            height += 1;
+        }
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_reader_should_parse_in_memory_input_skipping_blank_lines() {
+        let values: Vec<u32> = parse_reader(std::io::Cursor::new("1\n2\n\n3\n")).unwrap();
+        assert_eq!(vec![1, 2, 3], values);
+        let values: Vec<u32> = parse_reader("".as_bytes()).unwrap();
+        assert!(values.is_empty());
+    }
+
+    fn write_temp_input(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("aoc-2021-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn parse_input_strict_should_parse_all_lines_skipping_blank_ones() {
+        let path = write_temp_input("strict-ok.txt", "199\n200\n\n208\n");
+        let values: Vec<u32> = parse_input_strict(&path).unwrap();
+        assert_eq!(vec![199, 200, 208], values);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn parse_input_strict_should_report_the_line_that_fails_to_parse() {
+        let path = write_temp_input("strict-bad.txt", "199\n\n2O0\n208\n");
+        match parse_input_strict::<u32>(&path) {
+            Err(ParseInputError::InvalidLine { line_number, line }) => {
+                assert_eq!(3, line_number);
+                assert_eq!("2O0", line);
+            }
+            other => panic!("Expected an invalid line error, got {:?}", other),
+        }
+        // The lenient version still drops it
+        assert_eq!(vec![199, 208], parse_input::<u32>(&path).unwrap());
+        std::fs::remove_file(path).unwrap();
+    }
+
     #[test]
     fn bit_str_to_u64_should_return_0_for_empty_bit_str() {
         let s = "";
@@ -122,11 +511,64 @@ mod tests {
         assert_eq!(bit_str_to_u64(s), 25);
     }
 
+    #[test]
+    fn bit_str_to_u64_checked_should_accept_only_zeros_and_ones() {
+        assert_eq!(Ok(0), bit_str_to_u64_checked(""));
+        assert_eq!(Ok(25), bit_str_to_u64_checked("11001"));
+        assert_eq!(Ok(25), bit_str_to_u64_checked("  11001\n"));
+    }
+
+    #[test]
+    fn bit_str_to_u64_checked_should_return_the_first_invalid_char() {
+        assert_eq!(Err('x'), bit_str_to_u64_checked("1x01"));
+        assert_eq!(Err(' '), bit_str_to_u64_checked("10 01"));
+        assert_eq!(Err('2'), bit_str_to_u64_checked("1201y"));
+        // The lenient version reads them as zeros
+        assert_eq!(9, bit_str_to_u64("1x01"));
+    }
+
     #[test]
     fn test_hex_str_to_u8_vec() {
         assert_eq!(vec![0xd2, 0xfe, 0x2b], hex_str_to_u8_vec("D2FE2B"));
     }
 
+    #[test]
+    fn test_try_hex_str_to_u8_vec() {
+        assert_eq!(Ok(vec![0xd2, 0xfe, 0x2b]), try_hex_str_to_u8_vec("D2FE2B"));
+        assert_eq!(Ok(vec![0xd2, 0xfe, 0x2b]), try_hex_str_to_u8_vec("d2fe2b"));
+        assert_eq!(Ok(vec![]), try_hex_str_to_u8_vec(""));
+    }
+
+    #[test]
+    fn try_hex_str_to_u8_vec_should_reject_odd_length() {
+        assert_eq!(Err(HexError::OddLength(5)), try_hex_str_to_u8_vec("D2FE2"));
+    }
+
+    #[test]
+    fn try_hex_str_to_u8_vec_should_reject_invalid_digits() {
+        assert_eq!(
+            Err(HexError::InvalidDigit {
+                index: 3,
+                found: 'G'
+            }),
+            try_hex_str_to_u8_vec("D2FG2B")
+        );
+        assert_eq!(
+            Err(HexError::InvalidDigit {
+                index: 6,
+                found: '\n'
+            }),
+            try_hex_str_to_u8_vec("D2FE2B\n")
+        );
+    }
+
+    #[test]
+    fn test_u8_vec_to_hex_str() {
+        assert_eq!("D2FE2B", u8_vec_to_hex_str(&hex_str_to_u8_vec("D2FE2B")));
+        assert_eq!("000A", u8_vec_to_hex_str(&[0x00, 0x0a]));
+        assert_eq!("", u8_vec_to_hex_str(&[]));
+    }
+
     #[test]
     fn test_bit_extraction_from_single_byte() {
         let data = 0xD2;
@@ -135,6 +577,19 @@ mod tests {
         assert_eq!(0b1001, extract_bits_from_byte(data, 3, 4));
         assert_eq!(0b10010, extract_bits_from_byte(data, 3, 5));
         assert_eq!(0b11010010, extract_bits_from_byte(data, 0, 8));
+        assert_eq!(0b010, extract_bits_from_byte(data, 5, 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot extract 5 bits from bit offset 5 of a byte")]
+    fn bit_extraction_from_single_byte_should_reject_bits_past_the_end() {
+        extract_bits_from_byte(0xD2, 5, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot extract 0 bits from bit offset 0 of a byte")]
+    fn bit_extraction_from_single_byte_should_reject_zero_bits() {
+        extract_bits_from_byte(0xD2, 0, 0);
     }
 
     #[test]
@@ -152,7 +607,13 @@ mod tests {
     fn test_bit_extraction_from_multiple_bytes_invalid_start_bit() {
         let data = vec![0xD2, 0xFE, 0x28];
         // 1101 0010  1111 1110  0010 1000
-        assert_eq!(Err("invalid start_bit"), extract_bits(&data, 24, 1));
+        assert_eq!(
+            Err(ExtractError::StartBitOutOfRange {
+                start_bit: 24,
+                total_bits: 24,
+            }),
+            extract_bits(&data, 24, 1)
+        );
     }
 
     #[test]
@@ -160,20 +621,169 @@ mod tests {
         let data = vec![0xD2, 0xFE, 0x28];
         // 1101 0010  1111 1110  0010 1000
         assert_eq!(
-            Err("max value for bit_count is 64"),
+            Err(ExtractError::BitCountTooLarge(65)),
             extract_bits(&data, 0, 65)
         );
     }
 
+    #[test]
+    fn test_bit_extraction_from_multiple_bytes_zero_bit_count() {
+        let data = vec![0xD2, 0xFE, 0x28];
+        assert_eq!(Ok(0), extract_bits(&data, 3, 0));
+        assert_eq!(Ok(0), extract_bits_signed(&data, 3, 0));
+        let mut reader = BitReader::new(&data);
+        assert_eq!(Ok(0), reader.read(0));
+        assert_eq!(0, reader.position());
+    }
+
     #[test]
     fn test_bit_extraction_from_multiple_bytes_not_enough_bits() {
         let data = vec![0xD2, 0xFE, 0x28];
         // 1101 0010  1111 1110  0010 1000
         assert_eq!(
-            Err("not enough bits to extract"),
+            Err(ExtractError::NotEnoughBits {
+                requested: 25,
+                available: 24,
+            }),
             extract_bits(&data, 0, 25)
         );
     }
 
-}
+    #[test]
+    fn test_signed_bit_extraction() {
+        assert_eq!(Ok(-6), extract_bits_signed(&[0b1010_0000], 0, 4));
+        let data = vec![0xD2, 0xFE, 0x28];
+        // 1101 0010  1111 1110  0010 1000
+        assert_eq!(Ok(5), extract_bits_signed(&data, 5, 4));
+        assert_eq!(Ok(-5), extract_bits_signed(&data, 6, 4));
+        assert_eq!(Ok(-8), extract_bits_signed(&data, 12, 6));
+        assert_eq!(Ok(-1), extract_bits_signed(&[0xFF; 8], 0, 64));
+        assert_eq!(
+            Ok(i64::MAX),
+            extract_bits_signed(&[0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF], 0, 64)
+        );
+        assert_eq!(
+            Err(ExtractError::BitCountTooLarge(65)),
+            extract_bits_signed(&data, 0, 65)
+        );
+    }
 
+    #[test]
+    fn bit_reader_should_walk_a_literal_value_packet() {
+        let data = vec![0xD2, 0xFE, 0x28];
+        // 110 100 10111 11110 00101 000
+        let mut reader = BitReader::new(&data);
+        assert_eq!(Ok(6), reader.read(3));
+        assert_eq!(Ok(4), reader.read(3));
+        let mut value = 0;
+        loop {
+            let group = reader.read(5).unwrap();
+            value = (value << 4) | (group & 0b1111);
+            if group >> 4 == 0 {
+                break;
+            }
+        }
+        assert_eq!(2021, value);
+        assert_eq!(21, reader.position());
+        assert_eq!(3, reader.remaining());
+    }
+
+    #[test]
+    fn bit_reader_should_not_advance_past_a_failed_read() {
+        let data = vec![0xD2, 0xFE, 0x28];
+        let mut reader = BitReader::new(&data);
+        assert_eq!(Ok(0b1101001011111110), reader.read(16));
+        assert_eq!(
+            Err(BitError {
+                position: 16,
+                bit_count: 9,
+                reason: ExtractError::NotEnoughBits {
+                    requested: 9,
+                    available: 8,
+                },
+            }),
+            reader.read(9)
+        );
+        assert_eq!(16, reader.position());
+        assert_eq!(Ok(0b00101000), reader.read(8));
+        assert_eq!(0, reader.remaining());
+    }
+
+    #[test]
+    fn test_grid_from_digit_lines() {
+        let mut grid = Grid::from_digit_lines("2199\n3987\n9856\n");
+        assert_eq!(4, grid.width());
+        assert_eq!(3, grid.height());
+        assert_eq!(Some(&2), grid.get(0, 0));
+        assert_eq!(Some(&7), grid.get(1, 3));
+        assert_eq!(Some(&6), grid.get(2, 3));
+        assert_eq!(None, grid.get(3, 0));
+        assert_eq!(None, grid.get(0, 4));
+        *grid.get_mut(2, 1).unwrap() = 0;
+        assert_eq!(Some(&0), grid.get(2, 1));
+        assert_eq!(None, grid.get_mut(1, 4));
+    }
+
+    #[test]
+    #[should_panic(expected = "Row 1 has 3 cells; expected 4")]
+    fn grid_from_digit_lines_should_reject_ragged_rows() {
+        Grid::from_digit_lines("2199\n398\n");
+    }
+
+    #[test]
+    fn test_grid_of_other_types() {
+        let mut grid = Grid::new(3, 2, '.');
+        assert_eq!(3, grid.width());
+        assert_eq!(2, grid.height());
+        *grid.get_mut(1, 2).unwrap() = '#';
+        assert_eq!(Some(&'#'), grid.get(1, 2));
+        assert_eq!(Some(&'.'), grid.get(0, 2));
+        assert_eq!(3, grid.all_neighbors(1, 2).count());
+
+        let grid = Grid::from_vec(2, vec![true, false, false, true, true, true]);
+        assert_eq!(2, grid.width());
+        assert_eq!(3, grid.height());
+        assert_eq!(Some(&false), grid.get(1, 0));
+        assert_eq!(Some(&true), grid.get(2, 1));
+        assert_eq!(None, grid.get(3, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "5 cells do not fill rows of width 2")]
+    fn grid_from_vec_should_reject_partial_rows() {
+        Grid::from_vec(2, vec![1u32, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn grid_neighbors_of_a_corner_cell() {
+        let grid = Grid::from_digit_lines("123\n456\n789");
+        assert_eq!(
+            vec![(0, 1), (1, 0)],
+            grid.orthogonal_neighbors(0, 0).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(1, 1), (1, 2), (2, 1)],
+            grid.all_neighbors(2, 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn grid_neighbors_of_an_edge_cell() {
+        let grid = Grid::from_digit_lines("123\n456\n789");
+        assert_eq!(3, grid.orthogonal_neighbors(0, 1).count());
+        assert_eq!(5, grid.all_neighbors(0, 1).count());
+        assert_eq!(3, grid.orthogonal_neighbors(1, 2).count());
+        assert_eq!(5, grid.all_neighbors(1, 2).count());
+    }
+
+    #[test]
+    fn grid_neighbors_of_an_interior_cell() {
+        let grid = Grid::from_digit_lines("123\n456\n789");
+        assert_eq!(
+            vec![(0, 1), (1, 0), (1, 2), (2, 1)],
+            grid.orthogonal_neighbors(1, 1).collect::<Vec<_>>()
+        );
+        assert_eq!(8, grid.all_neighbors(1, 1).count());
+    }
+
+}